@@ -0,0 +1,137 @@
+//! Methods for aligning animation frames onto a shared canvas
+//!
+//! Frames in a group are different sizes, and are positioned by their image centers: a frame's
+//! anchor, the point drawn at the mobile's feet, sits at `(image_center_x, image_center_y + height)`
+//! in the frame's own coordinates. Drawing frames at their own sizes loses that relationship, so
+//! animations jitter when played back.
+//!
+//! An `AnimCanvas` is large enough to hold every frame it was built from, with every frame's
+//! anchor landing on the same pixel. Canvases can be combined, so that every direction of an
+//! action, or every action of a body, shares one size.
+use crate::anim::{AnimFrame, AnimGroup};
+#[cfg(feature = "image")]
+use crate::color::Color;
+#[cfg(feature = "image")]
+use image::{Rgba, RgbaImage};
+
+/// A canvas that frames of an animation can be drawn onto
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct AnimCanvas {
+    pub width: u32,
+    pub height: u32,
+    /// The pixel where each frame's anchor point is drawn.
+    /// This may fall just outside the canvas, as the anchor is usually below a frame's last row
+    pub anchor_x: i32,
+    pub anchor_y: i32,
+}
+
+impl AnimCanvas {
+    fn from_extents(left: i32, top: i32, right: i32, bottom: i32) -> AnimCanvas {
+        AnimCanvas {
+            width: (right - left) as u32,
+            height: (bottom - top) as u32,
+            anchor_x: -left,
+            anchor_y: -top,
+        }
+    }
+
+    /// The edges of this canvas, relative to the anchor
+    fn extents(&self) -> (i32, i32, i32, i32) {
+        let left = -self.anchor_x;
+        let top = -self.anchor_y;
+        (
+            left,
+            top,
+            left + self.width as i32,
+            top + self.height as i32,
+        )
+    }
+
+    /// Find the smallest canvas that holds every one of the given frames.
+    ///
+    /// Empty frames are skipped. If every frame is empty, a 1x1 canvas is returned
+    pub fn for_frames<'a, I: IntoIterator<Item = &'a AnimFrame>>(frames: I) -> AnimCanvas {
+        frames
+            .into_iter()
+            .filter(|frame| frame.width != 0 && frame.height != 0)
+            .map(|frame| {
+                let left = -(frame.image_center_x as i32);
+                let top = -(frame.image_center_y as i32) - frame.height as i32;
+                AnimCanvas::from_extents(
+                    left,
+                    top,
+                    left + frame.width as i32,
+                    top + frame.height as i32,
+                )
+            })
+            .reduce(|a, b| a.union(&b))
+            .unwrap_or(AnimCanvas {
+                width: 1,
+                height: 1,
+                anchor_x: 0,
+                anchor_y: 0,
+            })
+    }
+
+    /// Find the smallest canvas that holds every frame of a group
+    pub fn for_group(group: &AnimGroup) -> AnimCanvas {
+        AnimCanvas::for_frames(&group.frames)
+    }
+
+    /// Find the smallest canvas that holds every frame of several groups, such as each direction
+    /// of an action
+    pub fn for_groups<'a, I: IntoIterator<Item = &'a AnimGroup>>(groups: I) -> AnimCanvas {
+        AnimCanvas::for_frames(groups.into_iter().flat_map(|group| group.frames.iter()))
+    }
+
+    /// Find the smallest canvas that holds both this canvas and another, keeping anchors aligned
+    pub fn union(&self, other: &AnimCanvas) -> AnimCanvas {
+        let (l1, t1, r1, b1) = self.extents();
+        let (l2, t2, r2, b2) = other.extents();
+        AnimCanvas::from_extents(l1.min(l2), t1.min(t2), r1.max(r2), b1.max(b2))
+    }
+
+    /// Where the top left of a frame lands on this canvas
+    pub fn frame_origin(&self, frame: &AnimFrame) -> (i32, i32) {
+        (
+            self.anchor_x - frame.image_center_x as i32,
+            self.anchor_y - frame.image_center_y as i32 - frame.height as i32,
+        )
+    }
+
+    /// Draw a frame onto a new canvas-sized image, clipping anything that falls outside
+    #[cfg(feature = "image")]
+    pub fn render_frame(&self, frame: &AnimFrame, palette: &[u16]) -> RgbaImage {
+        let mut buffer = RgbaImage::new(self.width, self.height);
+        let (origin_x, origin_y) = self.frame_origin(frame);
+        for row in frame.data.iter() {
+            let y = row.y_offset(frame.image_center_y, frame.height as u32) + origin_y;
+            if y < 0 || y >= self.height as i32 {
+                continue;
+            }
+            let start = row.x_offset(frame.image_center_x) + origin_x;
+            for (i, &index) in row.image_data.iter().enumerate() {
+                let x = start + i as i32;
+                if x >= 0 && x < self.width as i32 {
+                    let (r, g, b, a) = palette[index as usize].to_rgba();
+                    buffer.put_pixel(x as u32, y as u32, Rgba([r, g, b, a]));
+                }
+            }
+        }
+        buffer
+    }
+}
+
+#[cfg(feature = "image")]
+impl AnimGroup {
+    /// Draw every frame onto the same canvas, so the anchor stays in one place.
+    ///
+    /// Use `AnimCanvas::for_group` for a canvas fitting just this group, or
+    /// `AnimCanvas::for_groups` to share one across several
+    pub fn to_aligned_frames(&self, canvas: &AnimCanvas) -> Vec<RgbaImage> {
+        self.frames
+            .iter()
+            .map(|frame| canvas.render_frame(frame, &self.palette))
+            .collect()
+    }
+}
@@ -0,0 +1,324 @@
+//! Methods for composing dressed and mounted mobiles out of several animations
+//!
+//! A mobile is drawn as its body animation, with the animation of each equipped item drawn over
+//! it. Equipment animations are found through the `anim_id` of the item's tiledata, used as a
+//! body id. Layers are drawn in a fixed order, which changes when the mobile faces away from the
+//! viewer so that cloaks and backpacks are drawn over the body rather than behind it.
+//!
+//! Mounted mobiles draw the mount first, then the rider, shifted by the mount's offset. Riders
+//! should use the mounted actions, which already contain the seated pose.
+//!
+//! Directions follow the client's numbering, from 0 (north, up and right on screen) clockwise to 7.
+//! Only 5 directions are stored, so the others are drawn mirrored.
+use crate::anim::canvas::AnimCanvas;
+use crate::anim::{AnimGroup, AnimReader};
+use crate::error::MulReaderResult;
+use crate::hue::{Hue, HueReader};
+use crate::tiledata::{Flags, Layer, TileDataReader};
+use image::RgbaImage;
+use image::imageops::{flip_horizontal, overlay};
+use std::io::{Read, Seek};
+
+/// The order layers are drawn in when facing the viewer. The body is always drawn first
+const FRONT_ORDER: [Layer; 24] = [
+    Layer::Backpack,
+    Layer::Cloak,
+    Layer::Shirt,
+    Layer::Pants,
+    Layer::Shoes,
+    Layer::InnerLegs,
+    Layer::InnerTorso,
+    Layer::Ring,
+    Layer::Talisman,
+    Layer::Bracelet,
+    Layer::Face,
+    Layer::Arms,
+    Layer::Gloves,
+    Layer::OuterLegs,
+    Layer::MiddleTorso,
+    Layer::Neck,
+    Layer::Hair,
+    Layer::OuterTorso,
+    Layer::Waist,
+    Layer::FacialHair,
+    Layer::Earrings,
+    Layer::Helm,
+    Layer::OneHanded,
+    Layer::TwoHanded,
+];
+
+/// The order layers are drawn in when facing away from the viewer
+const BACK_ORDER: [Layer; 24] = [
+    Layer::Shirt,
+    Layer::Pants,
+    Layer::Shoes,
+    Layer::InnerLegs,
+    Layer::InnerTorso,
+    Layer::Ring,
+    Layer::Talisman,
+    Layer::Bracelet,
+    Layer::Face,
+    Layer::Arms,
+    Layer::Gloves,
+    Layer::OuterLegs,
+    Layer::MiddleTorso,
+    Layer::Neck,
+    Layer::OuterTorso,
+    Layer::Waist,
+    Layer::FacialHair,
+    Layer::Earrings,
+    Layer::OneHanded,
+    Layer::TwoHanded,
+    Layer::Cloak,
+    Layer::Backpack,
+    Layer::Hair,
+    Layer::Helm,
+];
+
+/// Get the order equipment layers are drawn in for a direction
+pub fn layer_order(direction: u8) -> &'static [Layer] {
+    match direction & 7 {
+        6 | 7 | 0 => &BACK_ORDER,
+        _ => &FRONT_ORDER,
+    }
+}
+
+/// Find the stored direction used to draw a client direction, and whether it's mirrored
+pub fn file_direction(direction: u8) -> (u32, bool) {
+    match direction & 7 {
+        0 => (3, true),
+        1 => (2, true),
+        2 => (1, true),
+        3 => (0, false),
+        4 => (1, false),
+        5 => (2, false),
+        6 => (3, false),
+        _ => (4, false),
+    }
+}
+
+/// An item worn by a mobile
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Equipment {
+    /// The item's static tile id
+    pub item_id: u32,
+    /// The in-game hue, where 0 leaves the item unhued
+    pub hue: u16,
+}
+
+/// A mount, drawn beneath its rider
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Mount {
+    pub body: u32,
+    /// The in-game hue, where 0 leaves the mount unhued
+    pub hue: u16,
+    /// The mount's own action, such as walking or standing
+    pub action: u32,
+    /// How far the rider is shifted from the mount's anchor
+    pub rider_offset_x: i32,
+    pub rider_offset_y: i32,
+}
+
+/// Everything needed to draw a mobile
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct MobileAppearance {
+    pub body: u32,
+    /// The in-game hue of the body, where 0 leaves it unhued
+    pub hue: u16,
+    pub equipment: Vec<Equipment>,
+    pub mount: Option<Mount>,
+}
+
+/// Frames of a composed mobile, all drawn on the same canvas
+#[derive(Debug, Clone)]
+pub struct ComposedAnimation {
+    pub canvas: AnimCanvas,
+    pub frames: Vec<RgbaImage>,
+}
+
+/// A single animation to draw, already hued, and its offset from the shared anchor
+#[derive(Debug)]
+struct ComposeLayer {
+    group: AnimGroup,
+    offset: (i32, i32),
+}
+
+/// Builds composed animations out of animations, tiledata and hues
+#[derive(Debug)]
+pub struct Compositor<'a, A: Read + Seek, T: Read + Seek, H: Read + Seek> {
+    anims: &'a mut AnimReader<A>,
+    tiledata: &'a mut TileDataReader<T>,
+    hues: Option<&'a mut HueReader<H>>,
+}
+
+impl<'a, A: Read + Seek, T: Read + Seek, H: Read + Seek> Compositor<'a, A, T, H> {
+    /// Create a compositor. Without a HueReader, hues are ignored
+    pub fn new(
+        anims: &'a mut AnimReader<A>,
+        tiledata: &'a mut TileDataReader<T>,
+        hues: Option<&'a mut HueReader<H>>,
+    ) -> Compositor<'a, A, T, H> {
+        Compositor {
+            anims,
+            tiledata,
+            hues,
+        }
+    }
+
+    /// Look up an in-game hue, or None for unhued
+    fn read_hue(&mut self, hue: u16) -> MulReaderResult<Option<Hue>> {
+        //The top bits are flags rather than part of the hue number
+        let hue = hue & 0x3FFF;
+        match self.hues.as_mut() {
+            Some(reader) if hue != 0 => Ok(Some(reader.read_hue(hue as u32 - 1)?)),
+            _ => Ok(None),
+        }
+    }
+
+    fn read_layer(
+        &mut self,
+        body: u32,
+        action: u32,
+        direction: u32,
+        hue: u16,
+        partial: bool,
+    ) -> MulReaderResult<AnimGroup> {
+        let mut group = self.anims.read_body(body, action, direction)?;
+        if let Some(hue) = self.read_hue(hue)? {
+            for color in group.palette.iter_mut() {
+                *color = if partial {
+                    hue.apply_partial(*color)
+                } else {
+                    hue.apply(*color)
+                };
+            }
+        }
+        Ok(group)
+    }
+
+    /// Compose every frame of a mobile performing an action, facing a client direction.
+    ///
+    /// Fails if the body, mount or an item's tiledata can't be read. Equipment whose animation
+    /// can't be read for this action is skipped, as the client does
+    pub fn compose(
+        &mut self,
+        appearance: &MobileAppearance,
+        action: u32,
+        direction: u8,
+    ) -> MulReaderResult<ComposedAnimation> {
+        let (stored_direction, mirrored) = file_direction(direction);
+        let mut layers = vec![];
+        let mut rider_offset = (0, 0);
+
+        if let Some(mount) = appearance.mount {
+            let group =
+                self.read_layer(mount.body, mount.action, stored_direction, mount.hue, false)?;
+            layers.push(ComposeLayer {
+                group,
+                offset: (0, 0),
+            });
+            //Mirroring happens after composing, so offsets are always for the stored direction
+            rider_offset = (mount.rider_offset_x, mount.rider_offset_y);
+        }
+
+        let body = self.read_layer(
+            appearance.body,
+            action,
+            stored_direction,
+            appearance.hue,
+            false,
+        )?;
+        layers.push(ComposeLayer {
+            group: body,
+            offset: rider_offset,
+        });
+
+        let mut worn = vec![];
+        for equipment in appearance.equipment.iter() {
+            let tile = self.tiledata.read_static_tile_data(equipment.item_id)?;
+            if let Some(layer) = tile.layer()
+                && tile.anim_id != 0
+            {
+                worn.push((layer, tile.anim_id, equipment.hue, tile.flags));
+            }
+        }
+        for layer in layer_order(direction) {
+            for &(_, anim_id, hue, flags) in worn.iter().filter(|(worn, ..)| worn == layer) {
+                let partial = flags.contains(Flags::PartialHueFlag);
+                if let Ok(group) =
+                    self.read_layer(anim_id as u32, action, stored_direction, hue, partial)
+                {
+                    layers.push(ComposeLayer {
+                        group,
+                        offset: rider_offset,
+                    });
+                }
+            }
+        }
+
+        Ok(compose_layers(&layers, mirrored))
+    }
+}
+
+fn compose_layers(layers: &[ComposeLayer], mirrored: bool) -> ComposedAnimation {
+    //Shift each layer's canvas by its offset, so the union holds everything
+    let canvas = layers
+        .iter()
+        .map(|layer| {
+            let canvas = AnimCanvas::for_group(&layer.group);
+            AnimCanvas {
+                anchor_x: canvas.anchor_x - layer.offset.0,
+                anchor_y: canvas.anchor_y - layer.offset.1,
+                ..canvas
+            }
+        })
+        .reduce(|a, b| a.union(&b))
+        .unwrap_or(AnimCanvas {
+            width: 1,
+            height: 1,
+            anchor_x: 0,
+            anchor_y: 0,
+        });
+
+    let frame_count = layers
+        .iter()
+        .map(|layer| layer.group.frames.len())
+        .max()
+        .unwrap_or(0);
+    let mut frames = vec![];
+    for idx in 0..frame_count {
+        let mut buffer = RgbaImage::new(canvas.width, canvas.height);
+        for layer in layers.iter() {
+            if layer.group.frames.is_empty() {
+                continue;
+            }
+            //Layers with fewer frames loop, so a short mount cycle keeps up with its rider
+            let frame = &layer.group.frames[idx % layer.group.frames.len()];
+            let shifted = AnimCanvas {
+                anchor_x: canvas.anchor_x + layer.offset.0,
+                anchor_y: canvas.anchor_y + layer.offset.1,
+                ..canvas
+            };
+            overlay(
+                &mut buffer,
+                &shifted.render_frame(frame, &layer.group.palette),
+                0,
+                0,
+            );
+        }
+        if mirrored {
+            buffer = flip_horizontal(&buffer);
+        }
+        frames.push(buffer);
+    }
+
+    let canvas = if mirrored {
+        AnimCanvas {
+            anchor_x: canvas.width as i32 - canvas.anchor_x,
+            ..canvas
+        }
+    } else {
+        canvas
+    };
+    ComposedAnimation { canvas, frames }
+}
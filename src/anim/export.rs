@@ -0,0 +1,246 @@
+//! Methods for exporting animation groups as animated images and sprite sheets
+//!
+//! Frames are drawn onto a shared `AnimCanvas`, so they don't jitter when played back. By default
+//! the canvas fits the group being exported, but one can be supplied to keep several groups, such
+//! as every direction of an action, the same size.
+//!
+//! The client takes frame timings from animdata.mul, which isn't read yet, so delays are supplied
+//! through `ExportOptions`.
+use crate::anim::AnimGroup;
+use crate::anim::canvas::AnimCanvas;
+use crate::bounds::Bounds;
+use image::codecs::gif::{GifEncoder, Repeat};
+use image::error::{EncodingError, ImageFormatHint};
+use image::{Delay, Frame, ImageError, ImageFormat, RgbaImage};
+use std::io::Write;
+use std::time::Duration;
+
+/// How frames should be timed when exported
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct ExportOptions {
+    /// How long each frame is shown, unless overridden by `frame_delays`
+    pub delay: Duration,
+    /// Delays for individual frames, by position. Frames past the end use `delay`
+    pub frame_delays: Vec<Duration>,
+    /// Whether the animation repeats forever, or plays once
+    pub looping: bool,
+    /// The canvas to draw frames onto. If unset, the smallest canvas fitting the group is used
+    pub canvas: Option<AnimCanvas>,
+}
+
+impl Default for ExportOptions {
+    fn default() -> ExportOptions {
+        ExportOptions {
+            delay: Duration::from_millis(100),
+            frame_delays: vec![],
+            looping: true,
+            canvas: None,
+        }
+    }
+}
+
+impl ExportOptions {
+    fn delay_for(&self, frame: usize) -> Duration {
+        self.frame_delays.get(frame).copied().unwrap_or(self.delay)
+    }
+
+    fn canvas_for(&self, group: &AnimGroup) -> AnimCanvas {
+        self.canvas.unwrap_or_else(|| AnimCanvas::for_group(group))
+    }
+}
+
+/// A single frame's place on a sprite sheet
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct SheetFrame {
+    /// Where the frame is on the sheet
+    pub rect: Bounds,
+    /// Where the top left of the frame sits on the shared canvas
+    pub offset_x: i32,
+    pub offset_y: i32,
+    /// How long the frame is shown
+    pub duration: Duration,
+}
+
+/// Every frame of an animation, laid out left to right on a single image
+#[derive(Debug, Clone)]
+pub struct SpriteSheet {
+    pub image: RgbaImage,
+    pub frames: Vec<SheetFrame>,
+    /// The canvas that frame offsets are measured on
+    pub canvas: AnimCanvas,
+}
+
+impl SpriteSheet {
+    /// Describe the sheet in the JSON format used by Aseprite's "Hash" export, naming each frame
+    /// by its position. The canvas anchor is included in `meta` as a `pivot`
+    #[cfg(feature = "json")]
+    pub fn to_json(&self, image_name: &str) -> String {
+        use serde_json::{Map, Value, json};
+
+        let mut frames = Map::new();
+        for (idx, frame) in self.frames.iter().enumerate() {
+            frames.insert(
+                idx.to_string(),
+                json!({
+                    "frame": {
+                        "x": frame.rect.x,
+                        "y": frame.rect.y,
+                        "w": frame.rect.width,
+                        "h": frame.rect.height,
+                    },
+                    "rotated": false,
+                    "trimmed": true,
+                    "spriteSourceSize": {
+                        "x": frame.offset_x,
+                        "y": frame.offset_y,
+                        "w": frame.rect.width,
+                        "h": frame.rect.height,
+                    },
+                    "sourceSize": {"w": self.canvas.width, "h": self.canvas.height},
+                    "duration": frame.duration.as_millis() as u64,
+                }),
+            );
+        }
+        let sheet = json!({
+            "frames": Value::Object(frames),
+            "meta": {
+                "app": "uorustlibs",
+                "image": image_name,
+                "format": "RGBA8888",
+                "size": {"w": self.image.width(), "h": self.image.height()},
+                "scale": "1",
+                "pivot": {"x": self.canvas.anchor_x, "y": self.canvas.anchor_y},
+            },
+        });
+        serde_json::to_string_pretty(&sheet).expect("Failed to serialize JSON")
+    }
+}
+
+impl AnimGroup {
+    /// Render every frame onto a shared canvas, with delays applied
+    pub fn to_timed_frames(&self, options: &ExportOptions) -> Vec<Frame> {
+        let canvas = options.canvas_for(self);
+        self.to_aligned_frames(&canvas)
+            .into_iter()
+            .enumerate()
+            .map(|(idx, buffer)| {
+                Frame::from_parts(
+                    buffer,
+                    0,
+                    0,
+                    Delay::from_saturating_duration(options.delay_for(idx)),
+                )
+            })
+            .collect()
+    }
+
+    /// Write the group as an animated GIF.
+    ///
+    /// GIF delays are stored in hundredths of a second, so are rounded
+    pub fn write_gif<W: Write>(
+        &self,
+        writer: W,
+        options: &ExportOptions,
+    ) -> Result<(), ImageError> {
+        let mut encoder = GifEncoder::new(writer);
+        encoder.set_repeat(if options.looping {
+            Repeat::Infinite
+        } else {
+            Repeat::Finite(0)
+        })?;
+        encoder.encode_frames(self.to_timed_frames(options))
+    }
+
+    /// Write the group as an animated PNG.
+    ///
+    /// Delays are stored in milliseconds, up to a maximum of 65.535 seconds per frame
+    pub fn write_apng<W: Write>(
+        &self,
+        writer: W,
+        options: &ExportOptions,
+    ) -> Result<(), ImageError> {
+        let to_image_error = |e: png::EncodingError| {
+            ImageError::Encoding(EncodingError::new(
+                ImageFormatHint::Exact(ImageFormat::Png),
+                e,
+            ))
+        };
+        let frames = self.to_timed_frames(options);
+        let (width, height) = frames
+            .first()
+            .map(|frame| frame.buffer().dimensions())
+            .unwrap_or((1, 1));
+
+        let mut encoder = png::Encoder::new(writer, width, height);
+        encoder.set_color(png::ColorType::Rgba);
+        encoder.set_depth(png::BitDepth::Eight);
+        encoder
+            .set_animated(
+                frames.len().max(1) as u32,
+                if options.looping { 0 } else { 1 },
+            )
+            .map_err(to_image_error)?;
+        let mut png_writer = encoder.write_header().map_err(to_image_error)?;
+        if frames.is_empty() {
+            //An APNG needs at least one frame, so write a transparent one
+            png_writer
+                .write_image_data(RgbaImage::new(width, height).as_raw())
+                .map_err(to_image_error)?;
+        }
+        for (idx, frame) in frames.iter().enumerate() {
+            let millis = options.delay_for(idx).as_millis().min(u16::MAX as u128) as u16;
+            png_writer
+                .set_frame_delay(millis, 1000)
+                .map_err(to_image_error)?;
+            png_writer
+                .write_image_data(frame.buffer().as_raw())
+                .map_err(to_image_error)?;
+        }
+        png_writer.finish().map_err(to_image_error)
+    }
+
+    /// Lay out every frame left to right on a single image, recording where each one sits on the
+    /// shared canvas
+    pub fn to_sprite_sheet(&self, options: &ExportOptions) -> Result<SpriteSheet, ImageError> {
+        let canvas = options.canvas_for(self);
+        let mut rendered = vec![];
+        for frame in self.frames.iter() {
+            if frame.width == 0 || frame.height == 0 {
+                rendered.push(RgbaImage::new(0, 0));
+            } else {
+                rendered.push(frame.to_frame(&self.palette)?.into_buffer());
+            }
+        }
+
+        let width = rendered.iter().map(|image| image.width()).sum();
+        let height = rendered
+            .iter()
+            .map(|image| image.height())
+            .max()
+            .unwrap_or(0);
+        let mut image = RgbaImage::new(width, height);
+        let mut frames = vec![];
+        let mut x = 0;
+        for (idx, (frame, buffer)) in self.frames.iter().zip(rendered.iter()).enumerate() {
+            image::imageops::replace(&mut image, buffer, x as i64, 0);
+            let (offset_x, offset_y) = canvas.frame_origin(frame);
+            frames.push(SheetFrame {
+                rect: Bounds {
+                    x,
+                    y: 0,
+                    width: buffer.width(),
+                    height: buffer.height(),
+                },
+                offset_x,
+                offset_y,
+                duration: options.delay_for(idx),
+            });
+            x += buffer.width();
+        }
+        Ok(SpriteSheet {
+            image,
+            frames,
+            canvas,
+        })
+    }
+}
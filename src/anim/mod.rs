@@ -0,0 +1,780 @@
+//! Methods for reading animated characters out of anim.mul/anim.idx
+//!
+//! Animations are stored as a sequence of frames, with offsets.
+//! Inside these frames, a sequence rows. Rows store their offsets, allowing for compact
+//! representations of the data
+//!
+//! The underlying raw data for AnimationGroup is defined as
+//! `|palette:[u16..256]|frame_count:u32|frame_offsets:[u32..frame_count]|frames:[frame..frame_count]|`
+//!
+//! Frame offsets are read from the end of the palette to find individual frames
+//!
+//! The raw frame is defined as
+//!
+//! `|image_center_x:i16|image_center_y:i16|width:u16|height:u16|rows:[row..?]|`
+//!
+//! Each row has a header, which either contains offset information and length, or a special stop
+//! value of `0x7FFF7FFF`
+//!
+//! `|header:u32|pixels:[u8..?]|`
+//!
+//! Groups are indexed by body, action and direction. Low detail bodies (below 200) have 22
+//! actions, high detail bodies (200 to 399) have 13, and people and equipment (400 onwards) have
+//! 35. Each action has 5 directions, with the remaining 3 drawn by mirroring.
+//!
+//! `AnimReader::read_header` reads only the frame headers of a group, for when frame counts or
+//! sizes are needed without decoding every row.
+pub mod canvas;
+#[cfg(feature = "image")]
+pub mod compose;
+#[cfg(feature = "image")]
+pub mod export;
+
+use crate::bounds::{Bounds, BoundsBuilder};
+#[cfg(feature = "image")]
+use crate::color::Color;
+use crate::color::Color16;
+#[cfg(feature = "image")]
+use crate::error::{FromImageError, ToImageError};
+use crate::error::{
+    MEMWRITER_ERROR, MulReaderError, MulReaderResult, MulWriterError, MulWriterResult,
+};
+use crate::mul::{MulReader, MulWriter, MulWriterMode};
+use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
+#[cfg(feature = "image")]
+use image::error::{DecodingError, ImageError, ImageFormatHint};
+#[cfg(feature = "image")]
+use image::{Delay, Frame, Frames, Rgba, RgbaImage};
+#[cfg(feature = "image")]
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{Cursor, Read, Seek, SeekFrom, Write};
+use std::path::Path;
+#[cfg(feature = "image")]
+use std::time::Duration;
+
+const PALETTE_SIZE: usize = 256;
+const IMAGE_COMPLETE: u32 = 0x7FFF7FFF;
+
+const OFFSET_MASK: i32 = (0x200 << 22) | (0x200 << 12);
+const DIRECTIONS: u32 = 5;
+const MAX_RUN_LENGTH: usize = 0xFFF;
+
+/// Find the index of an animation group from a body, action and direction.
+///
+/// Returns None if the action or direction doesn't exist for that body
+pub fn group_index(body: u32, action: u32, direction: u32) -> Option<u32> {
+    let (base, first_body, actions) = match body {
+        0..200 => (0, 0, 22),
+        200..400 => (22000, 200, 13),
+        _ => (35000, 400, 35),
+    };
+    if action >= actions || direction >= DIRECTIONS {
+        return None;
+    }
+    (body - first_body)
+        .checked_mul(actions * DIRECTIONS)?
+        .checked_add(base + action * DIRECTIONS + direction)
+}
+
+/// A single row of a frame
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Row {
+    /// Compacted header information.
+    /// It contains the length of the associated data, plus offsets of where to plot, relative
+    /// to the image center
+    ///
+    /// `|x_offset..10bits|y_offset..10bits|run_length..12bits|`
+    ///
+    /// The offsets are signed values
+    pub header: u32,
+    /// Individual pixels for the row, as lookups in the AnimGroup palette
+    pub image_data: Vec<u8>,
+}
+
+impl Row {
+    /// Create a row that starts drawing at `x`, `y` in a frame with the given center and height.
+    ///
+    /// Returns None if the start is more than 512 pixels from the center, or the run is longer
+    /// than 4095 pixels, as neither can be stored in the header
+    pub fn new(
+        x: i32,
+        y: i32,
+        image_center_x: i16,
+        image_center_y: i16,
+        height: u32,
+        image_data: Vec<u8>,
+    ) -> Option<Row> {
+        let offset_x = x - image_center_x as i32;
+        let offset_y = y - image_center_y as i32 - height as i32;
+        let range = -0x200..0x200;
+        if !range.contains(&offset_x)
+            || !range.contains(&offset_y)
+            || image_data.len() > MAX_RUN_LENGTH
+        {
+            return None;
+        }
+        let header = (((offset_x & 0x3FF) as u32) << 22)
+            | (((offset_y & 0x3FF) as u32) << 12)
+            | image_data.len() as u32;
+        Some(Row { header, image_data })
+    }
+
+    /// Get the x offset of where to start drawing this row, relative to a center point
+    /// The resulting offset will be from the bottom left
+    pub fn x_offset(&self, image_center_x: i16) -> i32 {
+        (((self.header as i32 ^ OFFSET_MASK) >> 22) & 0x3FF) + image_center_x as i32 - 0x200
+    }
+
+    /// Get the y offset of where to start drawing this row, relative to a center point
+    /// The resulting offset will be from the bottom left
+    pub fn y_offset(&self, image_center_y: i16, height: u32) -> i32 {
+        (((self.header as i32 ^ OFFSET_MASK) >> 12) & 0x3FF) + image_center_y as i32 + height as i32
+            - 0x200
+    }
+
+    /// Convert this row back into its raw, storable form
+    pub fn serialize(&self) -> Vec<u8> {
+        let mut writer = vec![];
+        writer
+            .write_u32::<LittleEndian>(self.header)
+            .expect(MEMWRITER_ERROR);
+        writer
+            .write_all(self.image_data.as_slice())
+            .expect(MEMWRITER_ERROR);
+        writer
+    }
+
+    #[cfg(feature = "image")]
+    /// Draw a row into an image buffer.
+    ///
+    /// This is typically called by to_frame, and you won't need to call it directly
+    pub fn plot(
+        &self,
+        image_center_x: i16,
+        image_center_y: i16,
+        width: u16,
+        height: u16,
+        palette: &[u16],
+        buffer: &mut RgbaImage,
+    ) -> Result<(), ImageError> {
+        let x = self.x_offset(image_center_x);
+        let y = self.y_offset(image_center_y, height as u32);
+        if x < 0 || y < 0 || y >= height as i32 || x as u16 + self.image_data.len() as u16 > width {
+            return Err(ImageError::Decoding(DecodingError::new(
+                ImageFormatHint::Name("UO AnimFrame".to_string()),
+                ToImageError::PixelOutOfBounds {
+                    x: x as i64,
+                    y: y as i64,
+                },
+            )));
+        }
+        for i in 0..self.image_data.len() {
+            let target_x = x + i as i32;
+            if target_x > width as i32 {
+                return Err(ImageError::Decoding(DecodingError::new(
+                    ImageFormatHint::Name("UO AnimFrame".to_string()),
+                    ToImageError::PixelOutOfBounds {
+                        x: target_x as i64,
+                        y: y as i64,
+                    },
+                )));
+            }
+            let (r, g, b, a) = palette[self.image_data[i] as usize].to_rgba();
+            buffer.put_pixel(target_x as u32, y as u32, Rgba([r, g, b, a]));
+        }
+        Ok(())
+    }
+}
+
+/// A frame of an animtion
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct AnimFrame {
+    pub image_center_x: i16,
+    pub image_center_y: i16,
+    pub width: u16,
+    pub height: u16,
+    pub data: Vec<Row>,
+}
+
+impl AnimFrame {
+    /// Convert this frame back into its raw, storable form, ending with the stop value
+    pub fn serialize(&self) -> Vec<u8> {
+        let mut writer = vec![];
+        writer
+            .write_i16::<LittleEndian>(self.image_center_x)
+            .expect(MEMWRITER_ERROR);
+        writer
+            .write_i16::<LittleEndian>(self.image_center_y)
+            .expect(MEMWRITER_ERROR);
+        writer
+            .write_u16::<LittleEndian>(self.width)
+            .expect(MEMWRITER_ERROR);
+        writer
+            .write_u16::<LittleEndian>(self.height)
+            .expect(MEMWRITER_ERROR);
+        for row in self.data.iter() {
+            writer
+                .write_all(row.serialize().as_slice())
+                .expect(MEMWRITER_ERROR);
+        }
+        writer
+            .write_u32::<LittleEndian>(IMAGE_COMPLETE)
+            .expect(MEMWRITER_ERROR);
+        writer
+    }
+
+    /// Check whether a point falls on a drawn pixel, without rendering the frame.
+    ///
+    /// Coordinates are measured from the top left, as in `to_frame`
+    pub fn hit_test(&self, x: u32, y: u32) -> bool {
+        if x >= self.width as u32 || y >= self.height as u32 {
+            return false;
+        }
+        let (x, y) = (x as i32, y as i32);
+        self.data.iter().any(|row| {
+            let start = row.x_offset(self.image_center_x);
+            row.y_offset(self.image_center_y, self.height as u32) == y
+                && x >= start
+                && x < start + row.image_data.len() as i32
+        })
+    }
+
+    /// Get the smallest rectangle holding every drawn pixel, or None if the frame is empty
+    pub fn bounds(&self) -> Option<Bounds> {
+        let mut builder = BoundsBuilder::new(self.width as u32, self.height as u32);
+        for row in self.data.iter() {
+            builder.add_span(
+                row.x_offset(self.image_center_x) as i64,
+                row.y_offset(self.image_center_y, self.height as u32) as i64,
+                row.image_data.len() as i64,
+            );
+        }
+        builder.build()
+    }
+}
+
+#[cfg(feature = "image")]
+impl AnimFrame {
+    /// Convert an individual frame to an Image frame
+    pub fn to_frame(&self, palette: &[u16]) -> Result<Frame, ImageError> {
+        if self.width == 0 || self.height == 0 {
+            return Err(ImageError::Decoding(DecodingError::from_format_hint(
+                ImageFormatHint::Name("UO AnimFrame".to_string()),
+            )));
+        }
+        let mut buffer = RgbaImage::new(self.width as u32, self.height as u32);
+        for row in &self.data {
+            row.plot(
+                self.image_center_x,
+                self.image_center_y,
+                self.width,
+                self.height,
+                palette,
+                &mut buffer,
+            )?;
+        }
+        Ok(Frame::from_parts(
+            buffer,
+            0,
+            0,
+            Delay::from_saturating_duration(Duration::from_millis(0)),
+        ))
+    }
+}
+
+#[cfg(feature = "image")]
+impl AnimFrame {
+    /// Encode an image as a frame, with one row for each run of opaque pixels
+    fn from_image<F: FnMut(Color16) -> u8>(
+        image: &RgbaImage,
+        anchor: (i32, i32),
+        mut to_index: F,
+    ) -> Result<AnimFrame, FromImageError> {
+        let (width, height) = image.dimensions();
+        if width == 0 || width > u16::MAX as u32 || height == 0 || height > u16::MAX as u32 {
+            return Err(FromImageError::InvalidImageSize {
+                x: width,
+                y: height,
+            });
+        }
+        let out_of_range = |_| FromImageError::PixelOutOfRange {
+            x: anchor.0 as i64,
+            y: anchor.1 as i64,
+        };
+        let image_center_x = i16::try_from(anchor.0).map_err(out_of_range)?;
+        let image_center_y = i16::try_from(anchor.1 - height as i32).map_err(out_of_range)?;
+
+        let mut data = vec![];
+        for y in 0..height {
+            let mut x = 0;
+            while x < width {
+                if image.get_pixel(x, y).0[3] < 128 {
+                    x += 1;
+                    continue;
+                }
+                let start = x;
+                let mut image_data = vec![];
+                while x < width
+                    && image.get_pixel(x, y).0[3] >= 128
+                    && image_data.len() < MAX_RUN_LENGTH
+                {
+                    let Rgba([r, g, b, a]) = *image.get_pixel(x, y);
+                    image_data.push(to_index(Color16::from_rgba(r, g, b, a)));
+                    x += 1;
+                }
+                let row = Row::new(
+                    start as i32,
+                    y as i32,
+                    image_center_x,
+                    image_center_y,
+                    height,
+                    image_data,
+                )
+                .ok_or(FromImageError::PixelOutOfRange {
+                    x: start as i64,
+                    y: y as i64,
+                })?;
+                data.push(row);
+            }
+        }
+        Ok(AnimFrame {
+            image_center_x,
+            image_center_y,
+            width: width as u16,
+            height: height as u16,
+            data,
+        })
+    }
+}
+
+#[cfg(feature = "image")]
+fn channels(color: Color16) -> [i32; 3] {
+    [
+        ((color >> 10) & 0x1F) as i32,
+        ((color >> 5) & 0x1F) as i32,
+        (color & 0x1F) as i32,
+    ]
+}
+
+/// Find the index of the closest color in a palette
+#[cfg(feature = "image")]
+fn nearest_color(palette: &[Color16; 256], color: Color16) -> u8 {
+    let target = channels(color);
+    palette
+        .iter()
+        .enumerate()
+        .min_by_key(|(_, entry)| {
+            let entry = channels(**entry);
+            (0..3).map(|c| (entry[c] - target[c]).pow(2)).sum::<i32>()
+        })
+        .map(|(idx, _)| idx as u8)
+        .unwrap_or(0)
+}
+
+/// Build a palette covering the opaque pixels of every image, using median cut if there are
+/// more than 256 colors
+#[cfg(feature = "image")]
+pub fn quantise_palette(images: &[RgbaImage]) -> [Color16; 256] {
+    let mut counts: HashMap<Color16, u64> = HashMap::new();
+    for image in images {
+        for Rgba([r, g, b, a]) in image.pixels() {
+            if *a >= 128 {
+                *counts
+                    .entry(Color16::from_rgba(*r, *g, *b, *a))
+                    .or_default() += 1;
+            }
+        }
+    }
+    let mut colors: Vec<(Color16, u64)> = counts.into_iter().collect();
+    colors.sort();
+
+    let mut palette = [0; PALETTE_SIZE];
+    if colors.len() <= PALETTE_SIZE {
+        for (cell, (color, _)) in palette.iter_mut().zip(colors.iter()) {
+            *cell = *color;
+        }
+        return palette;
+    }
+
+    //Repeatedly split the box with the widest channel at its weighted median
+    let mut boxes = vec![colors];
+    while boxes.len() < PALETTE_SIZE {
+        let widest = boxes
+            .iter()
+            .enumerate()
+            .filter(|(_, colors)| colors.len() > 1)
+            .map(|(idx, colors)| {
+                let (channel, range) = (0..3)
+                    .map(|c| {
+                        let values = colors.iter().map(|(color, _)| channels(*color)[c]);
+                        (c, values.clone().max().unwrap() - values.min().unwrap())
+                    })
+                    .max_by_key(|(_, range)| *range)
+                    .unwrap();
+                (idx, channel, range)
+            })
+            .max_by_key(|(_, _, range)| *range);
+        let Some((idx, channel, _)) = widest else {
+            break;
+        };
+        let mut colors = boxes.swap_remove(idx);
+        colors.sort_by_key(|(color, _)| channels(*color)[channel]);
+        let total: u64 = colors.iter().map(|(_, count)| count).sum();
+        let mut seen = 0;
+        let mut split = 1;
+        for (i, (_, count)) in colors.iter().enumerate() {
+            seen += count;
+            if seen * 2 >= total {
+                split = (i + 1).clamp(1, colors.len() - 1);
+                break;
+            }
+        }
+        let upper = colors.split_off(split);
+        boxes.push(colors);
+        boxes.push(upper);
+    }
+
+    for (cell, colors) in palette.iter_mut().zip(boxes.iter()) {
+        let total: u64 = colors.iter().map(|(_, count)| count).sum();
+        let mut sums = [0u64; 3];
+        for (color, count) in colors.iter() {
+            for (sum, value) in sums.iter_mut().zip(channels(*color)) {
+                *sum += value as u64 * count;
+            }
+        }
+        let [r, g, b] = sums.map(|sum| ((sum + total / 2) / total) as u16);
+        *cell = (r << 10) | (g << 5) | b;
+    }
+    palette
+}
+
+/// An animation sequence
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct AnimGroup {
+    pub palette: [Color16; 256],
+    pub frame_count: u32,
+    pub frames: Vec<AnimFrame>,
+}
+
+impl AnimGroup {
+    /// Convert this group back into its raw, storable form.
+    ///
+    /// The frame count and offset table are generated from `frames`, so `frame_count` is ignored
+    pub fn serialize(&self) -> Vec<u8> {
+        let mut writer = vec![];
+        for &color in self.palette.iter() {
+            writer
+                .write_u16::<LittleEndian>(color)
+                .expect(MEMWRITER_ERROR);
+        }
+        let frames: Vec<Vec<u8>> = self.frames.iter().map(|frame| frame.serialize()).collect();
+        writer
+            .write_u32::<LittleEndian>(frames.len() as u32)
+            .expect(MEMWRITER_ERROR);
+
+        //Offsets are measured from the end of the palette
+        let mut offset = 4 + frames.len() as u32 * 4;
+        for frame in frames.iter() {
+            writer
+                .write_u32::<LittleEndian>(offset)
+                .expect(MEMWRITER_ERROR);
+            offset += frame.len() as u32;
+        }
+        for frame in frames.iter() {
+            writer.write_all(frame.as_slice()).expect(MEMWRITER_ERROR);
+        }
+        writer
+    }
+
+    /// Create an animation group from a sequence of images, sharing a palette built from every frame.
+    ///
+    /// The anchor is the point in each image which is drawn at the mobile's position, usually
+    /// between its feet. Pixels with an alpha below 128 are treated as transparent.
+    #[cfg(feature = "image")]
+    pub fn from_images(
+        images: &[RgbaImage],
+        anchor: (i32, i32),
+    ) -> Result<AnimGroup, FromImageError> {
+        let palette = quantise_palette(images);
+        AnimGroup::from_images_with_palette(images, anchor, &palette)
+    }
+
+    /// Create an animation group from a sequence of images, mapping each pixel to the nearest
+    /// color in an existing palette
+    #[cfg(feature = "image")]
+    pub fn from_images_with_palette(
+        images: &[RgbaImage],
+        anchor: (i32, i32),
+        palette: &[Color16; 256],
+    ) -> Result<AnimGroup, FromImageError> {
+        let mut lookup = HashMap::new();
+        let mut frames = vec![];
+        for image in images {
+            frames.push(AnimFrame::from_image(image, anchor, |color| {
+                *lookup
+                    .entry(color)
+                    .or_insert_with(|| nearest_color(palette, color))
+            })?);
+        }
+        Ok(AnimGroup {
+            palette: *palette,
+            frame_count: frames.len() as u32,
+            frames,
+        })
+    }
+
+    #[cfg(feature = "image")]
+    /// Convert an AnimGroup into Image-based frames.
+    ///
+    /// Frames are their own sizes and have no delays. Use `to_timed_frames` for frames that can
+    /// be played back
+    pub fn to_frames(&self) -> Frames<'_> {
+        Frames::new(Box::new(
+            self.frames
+                .iter()
+                .map(move |anim_frame| anim_frame.to_frame(&self.palette)),
+        ))
+    }
+}
+
+/// The header of a single frame, read without its pixel data
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct AnimFrameHeader {
+    pub image_center_x: i16,
+    pub image_center_y: i16,
+    pub width: u16,
+    pub height: u16,
+    /// Where the frame starts, in bytes from the start of the group's record
+    pub offset: u32,
+    /// How many bytes the frame takes up, up to the next frame or the end of the record
+    pub length: u32,
+}
+
+/// The headers of an animation group, read without decoding any rows.
+///
+/// This is enough to count frames or size click boxes. Individual frames can be decoded later
+/// with `AnimReader::read_frame`
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct AnimHeader {
+    /// Whether the palette holds any colors. Placeholder groups are often left all black
+    pub has_palette: bool,
+    pub frame_count: u32,
+    pub frames: Vec<AnimFrameHeader>,
+}
+
+/// A struct to allow reading of animations from data muls
+#[derive(Debug)]
+pub struct AnimReader<T: Read + Seek> {
+    mul_reader: MulReader<T>,
+}
+
+fn read_frame<T: Read + Seek>(reader: &mut T) -> MulReaderResult<AnimFrame> {
+    let image_center_x = reader.read_i16::<LittleEndian>()?;
+    let image_center_y = reader.read_i16::<LittleEndian>()?;
+    let width = reader.read_u16::<LittleEndian>()?;
+    let height = reader.read_u16::<LittleEndian>()?;
+
+    let mut data = vec![];
+    loop {
+        let header = reader.read_u32::<LittleEndian>()?;
+        if header == IMAGE_COMPLETE {
+            break;
+        }
+        let run_length = header & 0xFFF;
+        let mut image_data = vec![];
+        for _i in 0..run_length {
+            image_data.push(reader.read_u8()?);
+        }
+        data.push(Row { header, image_data });
+    }
+
+    // Read data
+    Ok(AnimFrame {
+        image_center_x,
+        image_center_y,
+        width,
+        height,
+        data,
+    })
+}
+
+impl AnimReader<File> {
+    /// Create an animation reader from paths to an index mul and a data mul
+    pub fn new(index_path: &Path, mul_path: &Path) -> MulReaderResult<AnimReader<File>> {
+        let mul_reader = MulReader::new(index_path, mul_path)?;
+        Ok(AnimReader { mul_reader })
+    }
+}
+
+impl<T: Read + Seek> AnimReader<T> {
+    /// Create an animation reader from an existing Mul
+    pub fn from_mul(reader: MulReader<T>) -> AnimReader<T> {
+        AnimReader { mul_reader: reader }
+    }
+
+    /// Read an animation group by id
+    pub fn read(&mut self, id: u32) -> MulReaderResult<AnimGroup> {
+        let raw = self.mul_reader.read(id)?;
+        let mut reader = Cursor::new(raw.data);
+        // Read the palette
+        let mut palette = [0; PALETTE_SIZE];
+        for cell in &mut palette {
+            *cell = reader.read_u16::<LittleEndian>()?;
+        }
+
+        let frame_count = reader.read_u32::<LittleEndian>()?;
+        let mut frame_offsets = vec![];
+        for _ in 0..frame_count {
+            frame_offsets.push(reader.read_u32::<LittleEndian>()?);
+        }
+
+        let mut frames = vec![];
+        for offset in frame_offsets {
+            reader.seek(SeekFrom::Start((PALETTE_SIZE as u32 * 2 + offset) as u64))?;
+            frames.push(read_frame(&mut reader)?);
+        }
+
+        Ok(AnimGroup {
+            palette,
+            frame_count,
+            frames,
+        })
+    }
+
+    /// Read the palette, frame count and frame headers of an animation group, without reading
+    /// any rows
+    pub fn read_header(&mut self, id: u32) -> MulReaderResult<AnimHeader> {
+        let palette_length = PALETTE_SIZE as u32 * 2;
+        let raw = self.mul_reader.read_part(id, 0, palette_length + 4)?;
+        let record_length = raw.length;
+        let mut reader = Cursor::new(raw.data);
+        let mut has_palette = false;
+        for _ in 0..PALETTE_SIZE {
+            has_palette |= reader.read_u16::<LittleEndian>()? != 0;
+        }
+        let frame_count = reader.read_u32::<LittleEndian>()?;
+
+        let raw =
+            self.mul_reader
+                .read_part(id, palette_length + 4, frame_count.saturating_mul(4))?;
+        let mut reader = Cursor::new(raw.data);
+        let mut offsets = vec![];
+        for _ in 0..frame_count {
+            offsets.push(palette_length + reader.read_u32::<LittleEndian>()?);
+        }
+
+        let mut frames = vec![];
+        for &offset in offsets.iter() {
+            let raw = self.mul_reader.read_part(id, offset, 8)?;
+            let mut reader = Cursor::new(raw.data);
+            //Frames are usually stored in order, but the offsets don't require it
+            let end = offsets
+                .iter()
+                .copied()
+                .filter(|&other| other > offset)
+                .min()
+                .unwrap_or(record_length);
+            frames.push(AnimFrameHeader {
+                image_center_x: reader.read_i16::<LittleEndian>()?,
+                image_center_y: reader.read_i16::<LittleEndian>()?,
+                width: reader.read_u16::<LittleEndian>()?,
+                height: reader.read_u16::<LittleEndian>()?,
+                offset,
+                length: end - offset,
+            });
+        }
+
+        Ok(AnimHeader {
+            has_palette,
+            frame_count,
+            frames,
+        })
+    }
+
+    /// Read just the palette of an animation group
+    pub fn read_palette(&mut self, id: u32) -> MulReaderResult<[Color16; 256]> {
+        let raw = self.mul_reader.read_part(id, 0, PALETTE_SIZE as u32 * 2)?;
+        let mut reader = Cursor::new(raw.data);
+        let mut palette = [0; PALETTE_SIZE];
+        for cell in &mut palette {
+            *cell = reader.read_u16::<LittleEndian>()?;
+        }
+        Ok(palette)
+    }
+
+    /// Decode a single frame of an animation group, using a header from `read_header`
+    pub fn read_frame(&mut self, id: u32, header: &AnimFrameHeader) -> MulReaderResult<AnimFrame> {
+        let raw = self
+            .mul_reader
+            .read_part(id, header.offset, header.length)?;
+        read_frame(&mut Cursor::new(raw.data))
+    }
+
+    /// Read the animation group for a body's action and direction
+    pub fn read_body(
+        &mut self,
+        body: u32,
+        action: u32,
+        direction: u32,
+    ) -> MulReaderResult<AnimGroup> {
+        let id =
+            group_index(body, action, direction).ok_or(MulReaderError::IndexOutOfBounds(body))?;
+        self.read(id)
+    }
+
+    /// Read every direction of a body's action, in direction order
+    pub fn read_directions(&mut self, body: u32, action: u32) -> MulReaderResult<Vec<AnimGroup>> {
+        (0..DIRECTIONS)
+            .map(|direction| self.read_body(body, action, direction))
+            .collect()
+    }
+}
+
+/// A struct to allow writing of animations to data muls
+#[derive(Debug)]
+pub struct AnimWriter<T: Write + Seek> {
+    mul_writer: MulWriter<T>,
+}
+
+impl AnimWriter<File> {
+    /// Create an animation writer from paths to an index mul and a data mul
+    pub fn new(
+        index_path: &Path,
+        mul_path: &Path,
+        mode: MulWriterMode,
+    ) -> MulWriterResult<AnimWriter<File>> {
+        let mul_writer = MulWriter::new(index_path, mul_path, mode)?;
+        Ok(AnimWriter { mul_writer })
+    }
+}
+
+impl<T: Write + Seek> AnimWriter<T> {
+    /// Create an animation writer from an existing Mul
+    pub fn from_mul(writer: MulWriter<T>) -> AnimWriter<T> {
+        AnimWriter { mul_writer: writer }
+    }
+
+    /// Write an animation group by id, replacing any existing group
+    pub fn write(&mut self, id: u32, group: &AnimGroup) -> MulWriterResult<()> {
+        self.mul_writer.write(id, &group.serialize(), None, None)
+    }
+
+    /// Write an animation group for a body's action and direction
+    pub fn write_body(
+        &mut self,
+        body: u32,
+        action: u32,
+        direction: u32,
+        group: &AnimGroup,
+    ) -> MulWriterResult<()> {
+        let id =
+            group_index(body, action, direction).ok_or(MulWriterError::IndexOutOfBounds(body))?;
+        self.write(id, group)
+    }
+
+    /// Remove an animation group from the index
+    pub fn delete(&mut self, id: u32) -> MulWriterResult<()> {
+        self.mul_writer.delete(id)
+    }
+}
@@ -1,8 +1,11 @@
-//! Methods for reading tile and static data out of art.mul
+//! Methods for reading and writing tile and static data in art.mul
 //!
 //! Tiles and Statics are both traditionally stored in art.mul/artidx.mul, and are packed into the
 //! same files - all entried before 0x4000 are tiles, and the rest statics.
 //!
+//! Older clients stop at 0xC000 statics, while later ones extend the index much further.
+//! The number of each available is taken from the size of the index.
+//!
 //! Map tiles are stored as
 //! `|header:u32|pixels:[u16..1022]|`
 //! Where pixels represents a list of rows, length 2, 4, 6, 8 .. 42, 44, 44, 42 .. 8, 6, 4, 2
@@ -19,11 +22,18 @@
 //! where the x_offset defines how many transparent pixels should be left before drawing this run.
 //!
 //! A run pair with an offset and length of 0 denotes that the row is complete.
-#[cfg(feature = "image")]
-use crate::color::Color;
+use crate::bounds::{Bounds, BoundsBuilder};
 use crate::color::Color16;
-use crate::error::{MEMWRITER_ERROR, MulReaderError, MulReaderResult};
-use crate::mul::MulReader;
+#[cfg(feature = "image")]
+use crate::color::{BLACK_16, Color, NEAR_BLACK_16};
+#[cfg(feature = "image")]
+use crate::error::FromImageError;
+use crate::error::{
+    MEMWRITER_ERROR, MulReaderError, MulReaderResult, MulWriterError, MulWriterResult,
+};
+#[cfg(feature = "image")]
+use crate::map::radarcol::RadarColWriter;
+use crate::mul::{MulReader, MulWriter, MulWriterMode};
 use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
 use std::fs::File;
 use std::io::{Cursor, Read, Seek, SeekFrom, Write};
@@ -43,6 +53,10 @@ pub trait Art {
 }
 
 pub const TILE_SIZE: u32 = 2048;
+/// Tiles are always 44px square
+pub const TILE_WIDTH: u32 = 44;
+/// The number of pixels in a tile's diamond
+const TILE_PIXELS: usize = 1012;
 pub const STATIC_OFFSET: u32 = 0x4000;
 
 /// A run pair contains an offset at which point to start drawing the run,
@@ -74,6 +88,33 @@ impl RunPair {
 
 pub type StaticRow = Vec<RunPair>;
 
+/// Average a set of colors, channel by channel
+fn average_color<'a, I: Iterator<Item = &'a Color16>>(colors: I) -> Color16 {
+    let mut totals = [0u64; 3];
+    let mut count = 0u64;
+    for color in colors {
+        totals[0] += ((color >> 10) & 0x1F) as u64;
+        totals[1] += ((color >> 5) & 0x1F) as u64;
+        totals[2] += (color & 0x1F) as u64;
+        count += 1;
+    }
+    if count == 0 {
+        return 0;
+    }
+    (((totals[0] / count) << 10) | ((totals[1] / count) << 5) | (totals[2] / count)) as Color16
+}
+
+/// Find where a row of a tile's diamond starts, and how wide it is
+fn tile_row_span(y: u32) -> (u32, u32) {
+    let half = TILE_WIDTH / 2;
+    let slice_size = if y >= half {
+        (TILE_WIDTH - y) * 2
+    } else {
+        (y + 1) * 2
+    };
+    (half - (slice_size / 2), slice_size)
+}
+
 /// A map tile, 44px by 44px.
 #[derive(Debug, PartialEq, Eq, Clone)]
 pub struct Tile {
@@ -84,6 +125,73 @@ pub struct Tile {
     pub image_data: [Color16; 1022],
 }
 
+impl Tile {
+    /// Check whether a point falls on the tile's diamond
+    pub fn hit_test(&self, x: u32, y: u32) -> bool {
+        if y >= TILE_WIDTH {
+            return false;
+        }
+        let (indent, slice_size) = tile_row_span(y);
+        x >= indent && x < indent + slice_size
+    }
+
+    /// Get the rectangle covering the tile's diamond
+    pub fn bounds(&self) -> Option<Bounds> {
+        let mut builder = BoundsBuilder::new(TILE_WIDTH, TILE_WIDTH);
+        for y in 0..TILE_WIDTH {
+            let (indent, slice_size) = tile_row_span(y);
+            builder.add_span(indent as i64, y as i64, slice_size as i64);
+        }
+        builder.build()
+    }
+
+    /// The average color of the diamond, as used in radarcol
+    pub fn average_color(&self) -> Color16 {
+        average_color(self.image_data[..TILE_PIXELS].iter())
+    }
+}
+
+#[cfg(feature = "image")]
+impl Tile {
+    /// Create a tile from a 44x44 image, reading the pixels inside the diamond.
+    ///
+    /// Alongside the tile, this returns the coordinates of any non-transparent pixels that fall
+    /// outside the diamond, and so were left out.
+    /// The diamond only covers 1012 pixels, so the remainder of `image_data` is left empty
+    pub fn from_image(image: &RgbaImage) -> Result<(Tile, Vec<(u32, u32)>), FromImageError> {
+        if image.width() != TILE_WIDTH || image.height() != TILE_WIDTH {
+            return Err(FromImageError::InvalidImageSize {
+                x: image.width(),
+                y: image.height(),
+            });
+        }
+
+        let mut image_data = [0; 1022];
+        let mut write_idx = 0;
+        let mut outside = vec![];
+        for y in 0..TILE_WIDTH {
+            let (indent, slice_size) = tile_row_span(y);
+            for x in 0..TILE_WIDTH {
+                let Rgba([r, g, b, a]) = *image.get_pixel(x, y);
+                if x >= indent && x < indent + slice_size {
+                    image_data[write_idx] = Color16::from_rgba(r, g, b, a);
+                    write_idx += 1;
+                } else if a != 0 {
+                    outside.push((x, y));
+                }
+            }
+        }
+
+        Ok((
+            Tile {
+                header: 0,
+                image_data,
+            },
+            outside,
+        ))
+    }
+}
+
 impl Art for Tile {
     fn serialize(&self) -> Vec<u8> {
         let mut writer = vec![];
@@ -100,13 +208,11 @@ impl Art for Tile {
 
     #[cfg(feature = "image")]
     fn to_image(&self) -> RgbaImage {
-        let mut buffer = RgbaImage::new(44, 44);
+        let mut buffer = RgbaImage::new(TILE_WIDTH, TILE_WIDTH);
         let mut read_idx = 0;
 
-        for y in 0..44 {
-            let slice_size = if y >= 22 { (44 - y) * 2 } else { (y + 1) * 2 };
-
-            let indent = 22 - (slice_size / 2);
+        for y in 0..TILE_WIDTH {
+            let (indent, slice_size) = tile_row_span(y);
 
             for x in 0..slice_size {
                 let (r, g, b, a) = self.image_data[read_idx].to_rgba();
@@ -156,7 +262,8 @@ impl Art for Static {
             lookup_table
                 .write_u16::<LittleEndian>(last_position)
                 .expect(MEMWRITER_ERROR);
-            last_position += (row.len() / 2) as u16;
+            //Offsets past u16::MAX can't be stored, from_image refuses to create such statics
+            last_position = last_position.wrapping_add((row.len() / 2) as u16);
         }
         writer
             .write_all(lookup_table.as_slice())
@@ -201,6 +308,132 @@ pub struct Static {
     pub rows: Vec<StaticRow>,
 }
 
+impl Static {
+    /// Check whether a point falls on an opaque pixel, without rendering the static
+    pub fn hit_test(&self, x: u32, y: u32) -> bool {
+        if x >= self.width as u32 {
+            return false;
+        }
+        let row = match self.rows.get(y as usize) {
+            Some(row) if y < self.height as u32 => row,
+            _ => return false,
+        };
+        let mut start = 0;
+        for run_pair in row.iter() {
+            start += run_pair.offset as u32;
+            if x < start {
+                return false;
+            }
+            if x < start + run_pair.run.len() as u32 {
+                return true;
+            }
+            start += run_pair.run.len() as u32;
+        }
+        false
+    }
+
+    /// Get the smallest rectangle holding every opaque pixel, or None if the static is empty
+    pub fn bounds(&self) -> Option<Bounds> {
+        let mut builder = BoundsBuilder::new(self.width as u32, self.height as u32);
+        for (y, row) in self.rows.iter().enumerate() {
+            let mut x = 0;
+            for run_pair in row.iter() {
+                x += run_pair.offset as i64;
+                builder.add_span(x, y as i64, run_pair.run.len() as i64);
+                x += run_pair.run.len() as i64;
+            }
+        }
+        builder.build()
+    }
+
+    /// The average color of the opaque pixels, as used in radarcol
+    pub fn average_color(&self) -> Color16 {
+        average_color(
+            self.rows
+                .iter()
+                .flat_map(|row| row.iter())
+                .flat_map(|pair| pair.run.iter()),
+        )
+    }
+}
+
+#[cfg(feature = "image")]
+impl Static {
+    /// Create a static from an image, run-length encoding each row.
+    ///
+    /// Pixels with an alpha below 128 are treated as transparent. As pure black is reserved for
+    /// transparency, black pixels are stored as `NEAR_BLACK_16` instead
+    pub fn from_image(image: &RgbaImage) -> Result<Static, FromImageError> {
+        let (width, height) = image.dimensions();
+        if width == 0 || width >= 1024 || height == 0 || height >= 1024 {
+            return Err(FromImageError::InvalidImageSize {
+                x: width,
+                y: height,
+            });
+        }
+
+        let mut rows = vec![];
+        for y in 0..height {
+            let mut row = vec![];
+            let mut gap = 0;
+            let mut run = vec![];
+            for x in 0..width {
+                let Rgba([r, g, b, a]) = *image.get_pixel(x, y);
+                if a < 0x80 {
+                    if !run.is_empty() {
+                        row.push(RunPair {
+                            offset: gap,
+                            run: std::mem::take(&mut run),
+                        });
+                        gap = 0;
+                    }
+                    gap += 1;
+                } else {
+                    let color = Color16::from_rgba(r, g, b, a);
+                    run.push(if color == BLACK_16 {
+                        NEAR_BLACK_16
+                    } else {
+                        color
+                    });
+                }
+            }
+            if !run.is_empty() {
+                row.push(RunPair { offset: gap, run });
+            }
+            rows.push(row);
+        }
+
+        //The lookup table stores each row's offset in words, which has to fit in a u16
+        let too_large = || FromImageError::InvalidImageSize {
+            x: width,
+            y: height,
+        };
+        let mut last_position: u16 = 0;
+        for row in rows.iter() {
+            //Each run is its offset and length followed by its pixels, then the row's newline
+            let words = row
+                .iter()
+                .try_fold(2u16, |words, pair| {
+                    u16::try_from(pair.run.len() + 2)
+                        .ok()
+                        .and_then(|run| words.checked_add(run))
+                })
+                .ok_or_else(too_large)?;
+            last_position = last_position.checked_add(words).ok_or_else(too_large)?;
+        }
+
+        let mut output = Static {
+            size: 0,
+            trigger: 0,
+            width: width as u16,
+            height: height as u16,
+            rows,
+        };
+        output.size = u16::try_from(output.serialize().len()).map_err(|_| too_large())?;
+        Ok(output)
+    }
+}
+
 /// A struct to help read out Tile and Static data
 #[derive(Debug)]
 pub struct ArtReader<T: Read + Seek> {
@@ -221,9 +454,19 @@ impl<T: Read + Seek> ArtReader<T> {
         ArtReader { mul_reader: reader }
     }
 
+    /// The number of tiles the index has room for
+    pub fn tile_count(&mut self) -> MulReaderResult<u32> {
+        Ok(self.mul_reader.entry_count()?.min(STATIC_OFFSET))
+    }
+
+    /// The number of statics the index has room for
+    pub fn static_count(&mut self) -> MulReaderResult<u32> {
+        Ok(self.mul_reader.entry_count()?.saturating_sub(STATIC_OFFSET))
+    }
+
     /// Read a single tile
     pub fn read_tile(&mut self, id: u32) -> MulReaderResult<Tile> {
-        if id >= STATIC_OFFSET {
+        if id >= self.tile_count()? {
             return Err(MulReaderError::IndexOutOfBounds(id));
         }
 
@@ -252,6 +495,9 @@ impl<T: Read + Seek> ArtReader<T> {
     ///
     /// Statics are read with an offset, so 0 is the first static in the file.
     pub fn read_static(&mut self, id: u32) -> MulReaderResult<Static> {
+        if id >= self.static_count()? {
+            return Err(MulReaderError::IndexOutOfBounds(id));
+        }
         let offset_id = id + STATIC_OFFSET;
 
         let raw = self.mul_reader.read(offset_id)?;
@@ -311,3 +557,132 @@ impl<T: Read + Seek> ArtReader<T> {
         })
     }
 }
+
+/// A struct to help write Tile and Static data
+#[derive(Debug)]
+pub struct ArtWriter<T: Write + Seek> {
+    mul_writer: MulWriter<T>,
+}
+
+impl ArtWriter<File> {
+    /// Create a new ArtWriter from an index and mul path
+    pub fn new(
+        index_path: &Path,
+        mul_path: &Path,
+        mode: MulWriterMode,
+    ) -> MulWriterResult<ArtWriter<File>> {
+        let mul_writer = MulWriter::new(index_path, mul_path, mode)?;
+        Ok(ArtWriter { mul_writer })
+    }
+}
+
+impl<T: Write + Seek> ArtWriter<T> {
+    /// Create an ArtWriter from an existing mul writer
+    pub fn from_mul(writer: MulWriter<T>) -> ArtWriter<T> {
+        ArtWriter { mul_writer: writer }
+    }
+
+    fn static_index(id: u32) -> MulWriterResult<u32> {
+        id.checked_add(STATIC_OFFSET)
+            .ok_or(MulWriterError::IndexOutOfBounds(id))
+    }
+
+    /// Write a single tile, replacing any existing tile with that id
+    pub fn write_tile(&mut self, id: u32, tile: &Tile) -> MulWriterResult<()> {
+        if id >= STATIC_OFFSET {
+            return Err(MulWriterError::IndexOutOfBounds(id));
+        }
+        self.mul_writer.write(id, &tile.serialize(), None, None)
+    }
+
+    /// Write a single static, replacing any existing static with that id.
+    ///
+    /// As with `ArtReader::read_static`, 0 is the first static in the file
+    pub fn write_static(&mut self, id: u32, art: &Static) -> MulWriterResult<()> {
+        let index = Self::static_index(id)?;
+        self.mul_writer.write(index, &art.serialize(), None, None)
+    }
+
+    /// Remove a tile from the index
+    pub fn delete_tile(&mut self, id: u32) -> MulWriterResult<()> {
+        if id >= STATIC_OFFSET {
+            return Err(MulWriterError::IndexOutOfBounds(id));
+        }
+        self.mul_writer.delete(id)
+    }
+
+    /// Remove a static from the index
+    pub fn delete_static(&mut self, id: u32) -> MulWriterResult<()> {
+        let index = Self::static_index(id)?;
+        self.mul_writer.delete(index)
+    }
+
+    /// Import every PNG in a directory as a tile, using the file name as a hex id
+    /// (such as `0x01A4.png` or `1a4.png`). Other files are skipped.
+    ///
+    /// Pixels outside each tile's diamond are ignored. If a RadarColWriter is passed, each tile's
+    /// average color is written to it. Returns the ids that were imported
+    #[cfg(feature = "image")]
+    pub fn import_tiles<U: Write + Seek>(
+        &mut self,
+        directory: &Path,
+        mut radarcol: Option<&mut RadarColWriter<U>>,
+    ) -> MulWriterResult<Vec<u32>> {
+        let images = find_images(directory)?;
+        for (id, path) in images.iter() {
+            let (tile, _outside) = Tile::from_image(&image::open(path)?.to_rgba8())?;
+            self.write_tile(*id, &tile)?;
+            if let Some(writer) = radarcol.as_mut() {
+                writer.write(*id, tile.average_color())?;
+            }
+        }
+        Ok(images.into_iter().map(|(id, _)| id).collect())
+    }
+
+    /// Import every PNG in a directory as a static, using the file name as a hex id
+    /// (such as `0x0EED.png` or `eed.png`). Other files are skipped.
+    ///
+    /// If a RadarColWriter is passed, each static's average color is written to it.
+    /// Returns the ids that were imported
+    #[cfg(feature = "image")]
+    pub fn import_statics<U: Write + Seek>(
+        &mut self,
+        directory: &Path,
+        mut radarcol: Option<&mut RadarColWriter<U>>,
+    ) -> MulWriterResult<Vec<u32>> {
+        let images = find_images(directory)?;
+        for (id, path) in images.iter() {
+            let art = Static::from_image(&image::open(path)?.to_rgba8())?;
+            self.write_static(*id, &art)?;
+            if let Some(writer) = radarcol.as_mut() {
+                writer.write(Self::static_index(*id)?, art.average_color())?;
+            }
+        }
+        Ok(images.into_iter().map(|(id, _)| id).collect())
+    }
+}
+
+/// Find all PNGs in a directory with hex names, sorted by id
+#[cfg(feature = "image")]
+fn find_images(directory: &Path) -> MulWriterResult<Vec<(u32, std::path::PathBuf)>> {
+    let mut images = vec![];
+    for entry in std::fs::read_dir(directory)? {
+        let path = entry?.path();
+        let is_png = path
+            .extension()
+            .map(|extension| extension.eq_ignore_ascii_case("png"))
+            .unwrap_or(false);
+        let stem = path.file_stem().and_then(|stem| stem.to_str());
+        if let (true, Some(stem)) = (is_png, stem) {
+            let hex = stem
+                .strip_prefix("0x")
+                .or_else(|| stem.strip_prefix("0X"))
+                .unwrap_or(stem);
+            if let Ok(id) = u32::from_str_radix(hex, 16) {
+                images.push((id, path));
+            }
+        }
+    }
+    images.sort();
+    Ok(images)
+}
@@ -0,0 +1,345 @@
+//! Methods for packing many assets into a few large texture atlas pages
+//!
+//! Assets are rendered, optionally trimmed down to their visible pixels, and packed onto pages
+//! no larger than a maximum size, using shelves filled tallest-first. Each packed asset gets an
+//! `AtlasEntry` recording its page, its rectangle, and an anchor point relative to that rectangle:
+//!
+//! * Tiles are anchored at the center of their diamond
+//! * Statics are anchored at the bottom center, where they meet the ground
+//! * Gumps and texmaps are anchored at the top left
+//!
+//! With the `json` feature, an atlas can describe itself as a JSON manifest.
+use crate::art::{Art, Static, TILE_WIDTH, Tile};
+use crate::bounds::Bounds;
+use crate::error::ToImageError;
+use crate::gump::Gump;
+use crate::texmap::TexMap;
+use image::{GenericImage, GenericImageView, ImageError, RgbaImage};
+
+/// The kind of asset held in an atlas entry, as ids are only unique within a kind
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy)]
+pub enum AssetKind {
+    Tile,
+    Static,
+    Gump,
+    TexMap,
+}
+
+impl AssetKind {
+    /// A lowercase name for the kind, as used in manifests
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AssetKind::Tile => "tile",
+            AssetKind::Static => "static",
+            AssetKind::Gump => "gump",
+            AssetKind::TexMap => "texmap",
+        }
+    }
+}
+
+/// Options to control atlas packing
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct AtlasOptions {
+    /// The widest a page may be
+    pub max_width: u32,
+    /// The tallest a page may be
+    pub max_height: u32,
+    /// Empty pixels left around every asset, to avoid bleeding when sampling
+    pub padding: u32,
+    /// Whether statics and gumps are trimmed down to their visible pixels
+    pub trim: bool,
+}
+
+impl Default for AtlasOptions {
+    fn default() -> AtlasOptions {
+        AtlasOptions {
+            max_width: 2048,
+            max_height: 2048,
+            padding: 1,
+            trim: true,
+        }
+    }
+}
+
+/// Where a single asset was packed
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct AtlasEntry {
+    pub kind: AssetKind,
+    pub id: u32,
+    /// The index of the page holding this asset
+    pub page: usize,
+    /// The packed rectangle on the page
+    pub rect: Bounds,
+    /// The area of the original image that was kept after trimming
+    pub source: Bounds,
+    /// The width of the original image
+    pub source_width: u32,
+    /// The height of the original image
+    pub source_height: u32,
+    /// The anchor point, relative to the top left of the packed rectangle.
+    /// Trimming may leave this outside the rectangle
+    pub anchor_x: i32,
+    pub anchor_y: i32,
+}
+
+/// A set of packed pages, and where each asset ended up
+#[derive(Debug, Clone)]
+pub struct Atlas {
+    pub pages: Vec<RgbaImage>,
+    pub entries: Vec<AtlasEntry>,
+}
+
+impl Atlas {
+    /// Find where an asset was packed
+    pub fn entry(&self, kind: AssetKind, id: u32) -> Option<&AtlasEntry> {
+        self.entries
+            .iter()
+            .find(|entry| entry.kind == kind && entry.id == id)
+    }
+
+    /// Describe the atlas as a JSON manifest, listing each page's size and each asset's rectangle
+    #[cfg(feature = "json")]
+    pub fn to_json(&self) -> String {
+        use serde_json::json;
+
+        let pages: Vec<_> = self
+            .pages
+            .iter()
+            .map(|page| json!({"width": page.width(), "height": page.height()}))
+            .collect();
+        let entries: Vec<_> = self
+            .entries
+            .iter()
+            .map(|entry| {
+                json!({
+                    "kind": entry.kind.as_str(),
+                    "id": entry.id,
+                    "page": entry.page,
+                    "x": entry.rect.x,
+                    "y": entry.rect.y,
+                    "width": entry.rect.width,
+                    "height": entry.rect.height,
+                    "source_x": entry.source.x,
+                    "source_y": entry.source.y,
+                    "source_width": entry.source_width,
+                    "source_height": entry.source_height,
+                    "anchor_x": entry.anchor_x,
+                    "anchor_y": entry.anchor_y,
+                })
+            })
+            .collect();
+        serde_json::to_string_pretty(&json!({"pages": pages, "entries": entries}))
+            .expect("Failed to serialize JSON")
+    }
+}
+
+#[derive(Debug)]
+struct PendingAsset {
+    kind: AssetKind,
+    id: u32,
+    image: RgbaImage,
+    source: Bounds,
+    anchor: (i32, i32),
+}
+
+#[derive(Debug)]
+struct Shelf {
+    y: u32,
+    height: u32,
+    next_x: u32,
+}
+
+#[derive(Debug, Default)]
+struct PageLayout {
+    shelves: Vec<Shelf>,
+    next_y: u32,
+    width: u32,
+    height: u32,
+}
+
+/// Collects assets, and packs them into an Atlas
+#[derive(Debug)]
+pub struct AtlasBuilder {
+    options: AtlasOptions,
+    assets: Vec<PendingAsset>,
+}
+
+impl AtlasBuilder {
+    /// Create an empty builder
+    pub fn new(options: AtlasOptions) -> AtlasBuilder {
+        AtlasBuilder {
+            options,
+            assets: vec![],
+        }
+    }
+
+    fn push(
+        &mut self,
+        kind: AssetKind,
+        id: u32,
+        image: RgbaImage,
+        bounds: Option<Option<Bounds>>,
+        anchor: (i32, i32),
+    ) {
+        let full = Bounds {
+            x: 0,
+            y: 0,
+            width: image.width(),
+            height: image.height(),
+        };
+        //Fully transparent assets still get an entry, trimmed down to a single pixel
+        let source = match bounds {
+            Some(Some(bounds)) => bounds,
+            Some(None) => Bounds {
+                width: full.width.min(1),
+                height: full.height.min(1),
+                ..full
+            },
+            None => full,
+        };
+        self.assets.push(PendingAsset {
+            kind,
+            id,
+            image,
+            source,
+            anchor,
+        });
+    }
+
+    /// Add a map tile, anchored at the center of its diamond
+    pub fn add_tile(&mut self, id: u32, tile: &Tile) {
+        let half = (TILE_WIDTH / 2) as i32;
+        self.push(AssetKind::Tile, id, tile.to_image(), None, (half, half));
+    }
+
+    /// Add a static, anchored at its bottom center
+    pub fn add_static(&mut self, id: u32, art: &Static) {
+        let bounds = self.options.trim.then(|| art.bounds());
+        let anchor = (art.width as i32 / 2, art.height as i32);
+        self.push(AssetKind::Static, id, art.to_image(), bounds, anchor);
+    }
+
+    /// Add a gump, anchored at its top left
+    pub fn add_gump(&mut self, id: u32, gump: &Gump) -> Result<(), ImageError> {
+        let bounds = self.options.trim.then(|| gump.bounds());
+        self.push(AssetKind::Gump, id, gump.to_image()?, bounds, (0, 0));
+        Ok(())
+    }
+
+    /// Add a texmap, anchored at its top left
+    pub fn add_texmap(&mut self, id: u32, texmap: &TexMap) {
+        self.push(AssetKind::TexMap, id, texmap.to_image(), None, (0, 0));
+    }
+
+    /// Find a spot for a rectangle, opening new shelves and pages as needed
+    fn place(&self, layouts: &mut Vec<PageLayout>, width: u32, height: u32) -> (usize, u32, u32) {
+        let padding = self.options.padding;
+        let fits_x = |x: u32| x + width + padding <= self.options.max_width;
+        for (page, layout) in layouts.iter_mut().enumerate() {
+            if let Some(shelf) = layout
+                .shelves
+                .iter_mut()
+                .find(|shelf| shelf.height >= height && fits_x(shelf.next_x))
+            {
+                let x = shelf.next_x;
+                shelf.next_x += width + padding;
+                return (page, x, shelf.y);
+            }
+            if layout.next_y + height + padding <= self.options.max_height {
+                let y = layout.next_y;
+                layout.shelves.push(Shelf {
+                    y,
+                    height,
+                    next_x: padding + width + padding,
+                });
+                layout.next_y += height + padding;
+                return (page, padding, y);
+            }
+        }
+        layouts.push(PageLayout {
+            shelves: vec![Shelf {
+                y: padding,
+                height,
+                next_x: padding + width + padding,
+            }],
+            next_y: padding + height + padding,
+            ..PageLayout::default()
+        });
+        (layouts.len() - 1, padding, padding)
+    }
+
+    /// Pack every added asset into pages.
+    ///
+    /// Pages are cropped to the space they use. Fails if any asset can't fit on a page of the
+    /// maximum size
+    pub fn build(mut self) -> Result<Atlas, ImageError> {
+        let padding = self.options.padding;
+        for asset in self.assets.iter() {
+            let (width, height) = (asset.source.width, asset.source.height);
+            if width + padding * 2 > self.options.max_width
+                || height + padding * 2 > self.options.max_height
+            {
+                return Err(ImageError::Encoding(image::error::EncodingError::new(
+                    image::error::ImageFormatHint::Name("UO Atlas".to_string()),
+                    ToImageError::InvalidImageSize {
+                        x: width,
+                        y: height,
+                    },
+                )));
+            }
+        }
+
+        //Tallest first keeps shelves tight, then order by id so output is stable
+        self.assets.sort_by(|a, b| {
+            b.source
+                .height
+                .cmp(&a.source.height)
+                .then(b.source.width.cmp(&a.source.width))
+                .then(a.kind.cmp(&b.kind))
+                .then(a.id.cmp(&b.id))
+        });
+
+        let mut layouts = vec![];
+        let mut entries = vec![];
+        for asset in self.assets.iter() {
+            let (width, height) = (asset.source.width, asset.source.height);
+            let (page, x, y) = self.place(&mut layouts, width, height);
+            let layout = &mut layouts[page];
+            layout.width = layout.width.max(x + width + padding);
+            layout.height = layout.height.max(y + height + padding);
+            entries.push(AtlasEntry {
+                kind: asset.kind,
+                id: asset.id,
+                page,
+                rect: Bounds {
+                    x,
+                    y,
+                    width,
+                    height,
+                },
+                source: asset.source,
+                source_width: asset.image.width(),
+                source_height: asset.image.height(),
+                anchor_x: asset.anchor.0 - asset.source.x as i32,
+                anchor_y: asset.anchor.1 - asset.source.y as i32,
+            });
+        }
+
+        let mut pages: Vec<RgbaImage> = layouts
+            .iter()
+            .map(|layout| RgbaImage::new(layout.width, layout.height))
+            .collect();
+        for (asset, entry) in self.assets.iter().zip(entries.iter()) {
+            let source = asset.image.view(
+                asset.source.x,
+                asset.source.y,
+                asset.source.width,
+                asset.source.height,
+            );
+            pages[entry.page].copy_from(&*source, entry.rect.x, entry.rect.y)?;
+        }
+
+        entries.sort_by_key(|entry| (entry.kind, entry.id));
+        Ok(Atlas { pages, entries })
+    }
+}
@@ -0,0 +1,64 @@
+//! Rectangles describing where the visible pixels of an asset lie
+//!
+//! Bounds are measured in the same space as the asset's `to_image` output, with the origin at the
+//! top left. Pixels which would fall outside the asset's width or height are never counted.
+
+/// A rectangle, with its origin at the top left
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Bounds {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl Bounds {
+    /// Check whether a point lies inside this rectangle
+    pub fn contains(&self, x: u32, y: u32) -> bool {
+        x >= self.x && y >= self.y && x - self.x < self.width && y - self.y < self.height
+    }
+}
+
+/// Grows a rectangle to cover horizontal spans of pixels, clipped to a canvas
+#[derive(Debug)]
+pub(crate) struct BoundsBuilder {
+    canvas_width: i64,
+    canvas_height: i64,
+    min: Option<(i64, i64)>,
+    max: (i64, i64),
+}
+
+impl BoundsBuilder {
+    pub(crate) fn new(canvas_width: u32, canvas_height: u32) -> BoundsBuilder {
+        BoundsBuilder {
+            canvas_width: canvas_width as i64,
+            canvas_height: canvas_height as i64,
+            min: None,
+            max: (0, 0),
+        }
+    }
+
+    /// Add a span of `length` pixels, starting at `x` on row `y`
+    pub(crate) fn add_span(&mut self, x: i64, y: i64, length: i64) {
+        if y < 0 || y >= self.canvas_height {
+            return;
+        }
+        let start = x.max(0);
+        let end = (x + length).min(self.canvas_width);
+        if start >= end {
+            return;
+        }
+        let (min_x, min_y) = self.min.unwrap_or((start, y));
+        self.min = Some((min_x.min(start), min_y.min(y)));
+        self.max = (self.max.0.max(end), self.max.1.max(y + 1));
+    }
+
+    pub(crate) fn build(self) -> Option<Bounds> {
+        self.min.map(|(x, y)| Bounds {
+            x: x as u32,
+            y: y as u32,
+            width: (self.max.0 - x) as u32,
+            height: (self.max.1 - y) as u32,
+        })
+    }
+}
@@ -12,6 +12,8 @@ pub type Color16 = u16;
 pub type Color32 = u32;
 
 pub const BLACK_16: Color16 = 0;
+/// The darkest color that isn't pure black, for formats where black is transparent
+pub const NEAR_BLACK_16: Color16 = 0x0421;
 
 impl Color for Color16 {
     fn to_rgba(&self) -> (u8, u8, u8, u8) {
@@ -18,6 +18,8 @@ pub enum MulReaderError {
     FailedParse(String),
     #[error("Coordinates {x}, {y} are out of bounds")]
     CoordinatesOutOfBounds { x: u32, y: u32 },
+    #[error("Invalid row {row}: {message}")]
+    InvalidRow { row: u32, message: String },
 }
 
 /// Errors that occur when writing muls
@@ -25,6 +27,19 @@ pub enum MulReaderError {
 pub enum MulWriterError {
     #[error("IO Error: {0}")]
     Io(#[from] std::io::Error),
+    #[error("Name {name:?} is longer than the maximum of {max} bytes")]
+    NameTooLong { name: String, max: usize },
+    #[error("Name {0:?} contains non-ASCII characters")]
+    NonAsciiName(String),
+    #[error("Trying to write out of bounds index {0}")]
+    IndexOutOfBounds(u32),
+    #[error("Got {found} entries, expected {expected}")]
+    UnexpectedSize { found: u32, expected: u32 },
+    #[error("Failed to convert image: {0}")]
+    FromImage(#[from] FromImageError),
+    #[cfg(feature = "image")]
+    #[error("Image Error: {0}")]
+    Image(#[from] image::ImageError),
 }
 
 pub type MulReaderResult<T> = std::result::Result<T, MulReaderError>;
@@ -38,3 +53,12 @@ pub enum ToImageError {
     #[error("Invalid image size of {x}, {y}")]
     InvalidImageSize { x: u32, y: u32 },
 }
+
+/// Errors that occur when trying to create Mul data from Image types
+#[derive(Error, Debug)]
+pub enum FromImageError {
+    #[error("Invalid image size of {x}, {y}")]
+    InvalidImageSize { x: u32, y: u32 },
+    #[error("Pixel {x}, {y} is too far from the anchor to be stored")]
+    PixelOutOfRange { x: i64, y: i64 },
+}
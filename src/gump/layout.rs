@@ -0,0 +1,586 @@
+//! Methods for parsing the layouts servers send to describe gumps
+//!
+//! A layout is a sequence of commands in braces, each a name followed by space-separated
+//! arguments, such as `{ page 0 }{ resizepic 0 0 9200 300 200 }{ text 20 20 0 0 }`. Text is sent
+//! separately as a list of lines, which commands refer to by index.
+//!
+//! Commands before the first `page` belong to page 0, which is drawn beneath every other page.
+//! Numbers are usually decimal, but `0x` prefixed hex is also accepted.
+use crate::error::{MulReaderError, MulReaderResult};
+
+/// What happens when a button is clicked
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum ButtonAction {
+    /// Switch to another page, without telling the server
+    Page(u32),
+    /// Close the gump, and reply to the server with a button id
+    Reply(u32),
+}
+
+/// A single layout command
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum GumpCommand {
+    NoMove,
+    NoClose,
+    NoDispose,
+    NoResize,
+    /// Start a new page. Everything up to the next page command is drawn on this page
+    Page(u32),
+    /// Start a new radio button group
+    Group(u32),
+    EndGroup,
+    /// A resizable background, made from nine gumps starting at `gump_id`
+    ResizePic {
+        x: i32,
+        y: i32,
+        gump_id: u32,
+        width: u32,
+        height: u32,
+    },
+    /// A single gump. `hue` is an in-game hue, where 0 leaves the gump unhued
+    GumpPic {
+        x: i32,
+        y: i32,
+        gump_id: u32,
+        hue: u16,
+    },
+    /// A gump repeated to fill a rectangle
+    GumpPicTiled {
+        x: i32,
+        y: i32,
+        width: u32,
+        height: u32,
+        gump_id: u32,
+    },
+    /// A static from art.mul
+    TilePic {
+        x: i32,
+        y: i32,
+        item_id: u32,
+    },
+    /// A hued static from art.mul
+    TilePicHue {
+        x: i32,
+        y: i32,
+        item_id: u32,
+        hue: u16,
+    },
+    /// A line of text
+    Text {
+        x: i32,
+        y: i32,
+        hue: u16,
+        text_id: u32,
+    },
+    /// A line of text, cut off at the edges of a rectangle
+    CroppedText {
+        x: i32,
+        y: i32,
+        width: u32,
+        height: u32,
+        hue: u16,
+        text_id: u32,
+    },
+    Button {
+        x: i32,
+        y: i32,
+        up_id: u32,
+        down_id: u32,
+        action: ButtonAction,
+    },
+    CheckBox {
+        x: i32,
+        y: i32,
+        off_id: u32,
+        on_id: u32,
+        checked: bool,
+        switch_id: u32,
+    },
+    /// A radio button, exclusive with the others in its group
+    Radio {
+        x: i32,
+        y: i32,
+        off_id: u32,
+        on_id: u32,
+        checked: bool,
+        switch_id: u32,
+    },
+    /// An editable text box, starting with a line of text. `max_length` is only set by
+    /// `textentrylimited`
+    TextEntry {
+        x: i32,
+        y: i32,
+        width: u32,
+        height: u32,
+        hue: u16,
+        entry_id: u32,
+        text_id: u32,
+        max_length: Option<u32>,
+    },
+    /// A box of HTML text
+    HtmlGump {
+        x: i32,
+        y: i32,
+        width: u32,
+        height: u32,
+        text_id: u32,
+        background: bool,
+        scrollbar: bool,
+    },
+    /// A box of HTML text from the client's localized strings. `color` is only set by
+    /// `xmfhtmlgumpcolor`
+    XmfHtmlGump {
+        x: i32,
+        y: i32,
+        width: u32,
+        height: u32,
+        cliloc: u32,
+        background: bool,
+        scrollbar: bool,
+        color: Option<u32>,
+    },
+    /// A rectangle which makes everything beneath it translucent
+    CheckerTrans {
+        x: i32,
+        y: i32,
+        width: u32,
+        height: u32,
+    },
+    /// A localized tooltip for the previous command
+    Tooltip(u32),
+    /// A command that isn't understood, kept as written
+    Unknown {
+        name: String,
+        args: Vec<String>,
+    },
+}
+
+impl GumpCommand {
+    /// Convert this command back into its braced, layout form
+    pub fn serialize(&self) -> String {
+        let flag = |value: bool| value as u32;
+        let body = match self {
+            GumpCommand::NoMove => "nomove".to_string(),
+            GumpCommand::NoClose => "noclose".to_string(),
+            GumpCommand::NoDispose => "nodispose".to_string(),
+            GumpCommand::NoResize => "noresize".to_string(),
+            GumpCommand::Page(page) => format!("page {}", page),
+            GumpCommand::Group(group) => format!("group {}", group),
+            GumpCommand::EndGroup => "endgroup".to_string(),
+            GumpCommand::ResizePic {
+                x,
+                y,
+                gump_id,
+                width,
+                height,
+            } => format!("resizepic {} {} {} {} {}", x, y, gump_id, width, height),
+            GumpCommand::GumpPic { x, y, gump_id, hue } => {
+                if *hue == 0 {
+                    format!("gumppic {} {} {}", x, y, gump_id)
+                } else {
+                    format!("gumppic {} {} {} hue={}", x, y, gump_id, hue)
+                }
+            }
+            GumpCommand::GumpPicTiled {
+                x,
+                y,
+                width,
+                height,
+                gump_id,
+            } => format!("gumppictiled {} {} {} {} {}", x, y, width, height, gump_id),
+            GumpCommand::TilePic { x, y, item_id } => format!("tilepic {} {} {}", x, y, item_id),
+            GumpCommand::TilePicHue { x, y, item_id, hue } => {
+                format!("tilepichue {} {} {} {}", x, y, item_id, hue)
+            }
+            GumpCommand::Text { x, y, hue, text_id } => {
+                format!("text {} {} {} {}", x, y, hue, text_id)
+            }
+            GumpCommand::CroppedText {
+                x,
+                y,
+                width,
+                height,
+                hue,
+                text_id,
+            } => format!(
+                "croppedtext {} {} {} {} {} {}",
+                x, y, width, height, hue, text_id
+            ),
+            GumpCommand::Button {
+                x,
+                y,
+                up_id,
+                down_id,
+                action,
+            } => {
+                let (kind, page, button_id) = match action {
+                    ButtonAction::Page(page) => (0, *page, 0),
+                    ButtonAction::Reply(button_id) => (1, 0, *button_id),
+                };
+                format!(
+                    "button {} {} {} {} {} {} {}",
+                    x, y, up_id, down_id, kind, page, button_id
+                )
+            }
+            GumpCommand::CheckBox {
+                x,
+                y,
+                off_id,
+                on_id,
+                checked,
+                switch_id,
+            } => format!(
+                "checkbox {} {} {} {} {} {}",
+                x,
+                y,
+                off_id,
+                on_id,
+                flag(*checked),
+                switch_id
+            ),
+            GumpCommand::Radio {
+                x,
+                y,
+                off_id,
+                on_id,
+                checked,
+                switch_id,
+            } => format!(
+                "radio {} {} {} {} {} {}",
+                x,
+                y,
+                off_id,
+                on_id,
+                flag(*checked),
+                switch_id
+            ),
+            GumpCommand::TextEntry {
+                x,
+                y,
+                width,
+                height,
+                hue,
+                entry_id,
+                text_id,
+                max_length,
+            } => match max_length {
+                Some(max_length) => format!(
+                    "textentrylimited {} {} {} {} {} {} {} {}",
+                    x, y, width, height, hue, entry_id, text_id, max_length
+                ),
+                None => format!(
+                    "textentry {} {} {} {} {} {} {}",
+                    x, y, width, height, hue, entry_id, text_id
+                ),
+            },
+            GumpCommand::HtmlGump {
+                x,
+                y,
+                width,
+                height,
+                text_id,
+                background,
+                scrollbar,
+            } => format!(
+                "htmlgump {} {} {} {} {} {} {}",
+                x,
+                y,
+                width,
+                height,
+                text_id,
+                flag(*background),
+                flag(*scrollbar)
+            ),
+            GumpCommand::XmfHtmlGump {
+                x,
+                y,
+                width,
+                height,
+                cliloc,
+                background,
+                scrollbar,
+                color,
+            } => {
+                let common = format!(
+                    "{} {} {} {} {} {} {}",
+                    x,
+                    y,
+                    width,
+                    height,
+                    cliloc,
+                    flag(*background),
+                    flag(*scrollbar)
+                );
+                match color {
+                    Some(color) => format!("xmfhtmlgumpcolor {} {}", common, color),
+                    None => format!("xmfhtmlgump {}", common),
+                }
+            }
+            GumpCommand::CheckerTrans {
+                x,
+                y,
+                width,
+                height,
+            } => format!("checkertrans {} {} {} {}", x, y, width, height),
+            GumpCommand::Tooltip(cliloc) => format!("tooltip {}", cliloc),
+            GumpCommand::Unknown { name, args } => std::iter::once(name.as_str())
+                .chain(args.iter().map(|arg| arg.as_str()))
+                .collect::<Vec<_>>()
+                .join(" "),
+        };
+        format!("{{ {} }}", body)
+    }
+}
+
+/// A parsed layout and its text lines
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct GumpLayout {
+    pub commands: Vec<GumpCommand>,
+    pub text: Vec<String>,
+}
+
+impl GumpLayout {
+    /// Parse a layout string, keeping the text lines it refers to.
+    ///
+    /// Unrecognised commands are kept as `GumpCommand::Unknown`, but known commands with missing
+    /// or malformed arguments, and unbalanced braces, fail to parse
+    pub fn parse(layout: &str, text: Vec<String>) -> MulReaderResult<GumpLayout> {
+        let mut commands = vec![];
+        let mut rest = layout;
+        while let Some(start) = rest.find('{') {
+            let after = &rest[start + 1..];
+            let end = after.find('}').ok_or_else(|| {
+                MulReaderError::FailedParse(format!("Unclosed command in {:?}", &rest[start..]))
+            })?;
+            let tokens: Vec<&str> = after[..end].split_whitespace().collect();
+            if !tokens.is_empty() {
+                commands.push(parse_command(&tokens)?);
+            }
+            rest = &after[end + 1..];
+        }
+        if rest.contains('}') {
+            return Err(MulReaderError::FailedParse(format!(
+                "Unopened command in {:?}",
+                rest
+            )));
+        }
+        Ok(GumpLayout { commands, text })
+    }
+
+    /// Convert the commands back into a layout string. Text lines are kept separately
+    pub fn serialize(&self) -> String {
+        self.commands.iter().map(GumpCommand::serialize).collect()
+    }
+
+    /// The numbers of every page, in the order they first appear. Page 0 is always included
+    pub fn pages(&self) -> Vec<u32> {
+        let mut pages = vec![0];
+        for command in self.commands.iter() {
+            if let GumpCommand::Page(page) = command
+                && !pages.contains(page)
+            {
+                pages.push(*page);
+            }
+        }
+        pages
+    }
+
+    /// The commands drawn on a page, including those of page 0 beneath them
+    pub fn page_commands(&self, page: u32) -> Vec<&GumpCommand> {
+        let mut current = 0;
+        let mut output = vec![];
+        for command in self.commands.iter() {
+            if let GumpCommand::Page(next) = command {
+                current = *next;
+            } else if current == 0 || current == page {
+                output.push(command);
+            }
+        }
+        output
+    }
+
+    /// Get a line of text, or an empty string if there's no line with that id
+    pub fn text_line(&self, text_id: u32) -> &str {
+        self.text
+            .get(text_id as usize)
+            .map(|line| line.as_str())
+            .unwrap_or("")
+    }
+}
+
+/// Parse a decimal or hex number
+fn parse_number(token: &str) -> Option<i64> {
+    let (negative, digits) = match token.strip_prefix('-') {
+        Some(digits) => (true, digits),
+        None => (false, token),
+    };
+    let value = match digits
+        .strip_prefix("0x")
+        .or_else(|| digits.strip_prefix("0X"))
+    {
+        Some(hex) => i64::from_str_radix(hex, 16).ok()?,
+        None => digits.parse::<i64>().ok()?,
+    };
+    Some(if negative { -value } else { value })
+}
+
+/// Parse a single argument of a command
+fn arg<T: TryFrom<i64>>(tokens: &[&str], idx: usize) -> MulReaderResult<T> {
+    let token = tokens.get(idx).ok_or_else(|| {
+        MulReaderError::FailedParse(format!(
+            "{} is missing argument {}",
+            tokens[0],
+            idx.saturating_sub(1)
+        ))
+    })?;
+    parse_number(token)
+        .and_then(|value| T::try_from(value).ok())
+        .ok_or_else(|| {
+            MulReaderError::FailedParse(format!("Invalid argument {:?} to {}", token, tokens[0]))
+        })
+}
+
+fn parse_command(tokens: &[&str]) -> MulReaderResult<GumpCommand> {
+    let flag = |idx| arg::<i64>(tokens, idx).map(|value| value != 0);
+    Ok(match tokens[0].to_ascii_lowercase().as_str() {
+        "nomove" => GumpCommand::NoMove,
+        "noclose" => GumpCommand::NoClose,
+        "nodispose" => GumpCommand::NoDispose,
+        "noresize" => GumpCommand::NoResize,
+        "page" => GumpCommand::Page(arg(tokens, 1)?),
+        "group" => GumpCommand::Group(arg(tokens, 1)?),
+        "endgroup" => GumpCommand::EndGroup,
+        "resizepic" => GumpCommand::ResizePic {
+            x: arg(tokens, 1)?,
+            y: arg(tokens, 2)?,
+            gump_id: arg(tokens, 3)?,
+            width: arg(tokens, 4)?,
+            height: arg(tokens, 5)?,
+        },
+        "gumppic" => {
+            //Extra arguments are named, such as hue=33
+            let mut hue = 0;
+            for token in tokens.iter().skip(4) {
+                if let Some(value) = token.to_ascii_lowercase().strip_prefix("hue=") {
+                    hue = arg(&[tokens[0], value], 1)?;
+                }
+            }
+            GumpCommand::GumpPic {
+                x: arg(tokens, 1)?,
+                y: arg(tokens, 2)?,
+                gump_id: arg(tokens, 3)?,
+                hue,
+            }
+        }
+        "gumppictiled" => GumpCommand::GumpPicTiled {
+            x: arg(tokens, 1)?,
+            y: arg(tokens, 2)?,
+            width: arg(tokens, 3)?,
+            height: arg(tokens, 4)?,
+            gump_id: arg(tokens, 5)?,
+        },
+        "tilepic" => GumpCommand::TilePic {
+            x: arg(tokens, 1)?,
+            y: arg(tokens, 2)?,
+            item_id: arg(tokens, 3)?,
+        },
+        "tilepichue" => GumpCommand::TilePicHue {
+            x: arg(tokens, 1)?,
+            y: arg(tokens, 2)?,
+            item_id: arg(tokens, 3)?,
+            hue: arg(tokens, 4)?,
+        },
+        "text" => GumpCommand::Text {
+            x: arg(tokens, 1)?,
+            y: arg(tokens, 2)?,
+            hue: arg(tokens, 3)?,
+            text_id: arg(tokens, 4)?,
+        },
+        "croppedtext" => GumpCommand::CroppedText {
+            x: arg(tokens, 1)?,
+            y: arg(tokens, 2)?,
+            width: arg(tokens, 3)?,
+            height: arg(tokens, 4)?,
+            hue: arg(tokens, 5)?,
+            text_id: arg(tokens, 6)?,
+        },
+        "button" => {
+            let action = if arg::<u32>(tokens, 5)? == 0 {
+                ButtonAction::Page(arg(tokens, 6)?)
+            } else {
+                ButtonAction::Reply(arg(tokens, 7)?)
+            };
+            GumpCommand::Button {
+                x: arg(tokens, 1)?,
+                y: arg(tokens, 2)?,
+                up_id: arg(tokens, 3)?,
+                down_id: arg(tokens, 4)?,
+                action,
+            }
+        }
+        "checkbox" => GumpCommand::CheckBox {
+            x: arg(tokens, 1)?,
+            y: arg(tokens, 2)?,
+            off_id: arg(tokens, 3)?,
+            on_id: arg(tokens, 4)?,
+            checked: flag(5)?,
+            switch_id: arg(tokens, 6)?,
+        },
+        "radio" => GumpCommand::Radio {
+            x: arg(tokens, 1)?,
+            y: arg(tokens, 2)?,
+            off_id: arg(tokens, 3)?,
+            on_id: arg(tokens, 4)?,
+            checked: flag(5)?,
+            switch_id: arg(tokens, 6)?,
+        },
+        name @ ("textentry" | "textentrylimited") => GumpCommand::TextEntry {
+            x: arg(tokens, 1)?,
+            y: arg(tokens, 2)?,
+            width: arg(tokens, 3)?,
+            height: arg(tokens, 4)?,
+            hue: arg(tokens, 5)?,
+            entry_id: arg(tokens, 6)?,
+            text_id: arg(tokens, 7)?,
+            max_length: if name == "textentrylimited" {
+                Some(arg(tokens, 8)?)
+            } else {
+                None
+            },
+        },
+        "htmlgump" => GumpCommand::HtmlGump {
+            x: arg(tokens, 1)?,
+            y: arg(tokens, 2)?,
+            width: arg(tokens, 3)?,
+            height: arg(tokens, 4)?,
+            text_id: arg(tokens, 5)?,
+            background: flag(6)?,
+            scrollbar: flag(7)?,
+        },
+        name @ ("xmfhtmlgump" | "xmfhtmlgumpcolor") => GumpCommand::XmfHtmlGump {
+            x: arg(tokens, 1)?,
+            y: arg(tokens, 2)?,
+            width: arg(tokens, 3)?,
+            height: arg(tokens, 4)?,
+            cliloc: arg(tokens, 5)?,
+            background: flag(6)?,
+            scrollbar: flag(7)?,
+            color: if name == "xmfhtmlgumpcolor" {
+                Some(arg(tokens, 8)?)
+            } else {
+                None
+            },
+        },
+        "checkertrans" => GumpCommand::CheckerTrans {
+            x: arg(tokens, 1)?,
+            y: arg(tokens, 2)?,
+            width: arg(tokens, 3)?,
+            height: arg(tokens, 4)?,
+        },
+        "tooltip" => GumpCommand::Tooltip(arg(tokens, 1)?),
+        _ => GumpCommand::Unknown {
+            name: tokens[0].to_string(),
+            args: tokens[1..].iter().map(|token| token.to_string()).collect(),
+        },
+    })
+}
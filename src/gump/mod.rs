@@ -0,0 +1,430 @@
+//! Methods for reading and writing data in gumpart.mul and gumpidx.mul
+//!
+//! Gumps represent GUI elements, and actually use the opt fields in the index mul:
+//! `opt1` representing height, and `opt2` representing width
+//!
+//! The gump itself is stored as such:
+//! `|offsets:[u32..height]|rows:[row..height]|`
+//!
+//! The offsets are counted in 4 byte steps from the start of the gump, and are also used to
+//! calculate the length of a given row
+//!
+//! A row is defined as a number of RLE pairs:
+//!
+//! `|color:Color16|count:u16|`
+//!
+//! Resizable backgrounds are made of nine consecutive gumps, which can be read together with
+//! `GumpReader::read_resizepic` and drawn at any size
+//!
+//! Layouts sent by servers are parsed in `layout`, and can be previewed with `render`. The
+//! packets carrying them are handled in `packet`
+pub mod layout;
+pub mod packet;
+#[cfg(feature = "image")]
+pub mod render;
+
+use crate::bounds::{Bounds, BoundsBuilder};
+#[cfg(feature = "image")]
+use crate::color::Color;
+#[cfg(feature = "image")]
+use crate::color::NEAR_BLACK_16;
+use crate::color::{BLACK_16, Color16};
+#[cfg(feature = "image")]
+use crate::error::{FromImageError, ToImageError};
+use crate::error::{MEMWRITER_ERROR, MulReaderError, MulReaderResult, MulWriterResult};
+use crate::mul::{MulReader, MulWriter, MulWriterMode};
+use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
+#[cfg(feature = "image")]
+use image::error::{DecodingError, ImageFormatHint};
+#[cfg(feature = "image")]
+use image::{ImageError, Rgba, RgbaImage};
+use std::fs::File;
+use std::io::{Cursor, Read, Seek, SeekFrom, Write};
+use std::path::Path;
+
+/// An RLE pair
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct GumpPair {
+    pub color: Color16,
+    pub count: u16,
+}
+
+/// A user-interface element.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Gump {
+    pub width: u16,
+    pub height: u16,
+    /// Rows of RLE pairs
+    pub data: Vec<Vec<GumpPair>>,
+}
+
+impl Gump {
+    /// Check whether a point falls on an opaque pixel, without rendering the gump
+    pub fn hit_test(&self, x: u32, y: u32) -> bool {
+        if x >= self.width as u32 {
+            return false;
+        }
+        let row = match self.data.get(y as usize) {
+            Some(row) if y < self.height as u32 => row,
+            _ => return false,
+        };
+        let mut start = 0;
+        for run_pair in row.iter() {
+            let end = start + run_pair.count as u32;
+            if x < end {
+                // Pure black is Transparent in Gumps
+                return run_pair.color != BLACK_16;
+            }
+            start = end;
+        }
+        false
+    }
+
+    /// Get the smallest rectangle holding every opaque pixel, or None if the gump is empty
+    pub fn bounds(&self) -> Option<Bounds> {
+        let mut builder = BoundsBuilder::new(self.width as u32, self.height as u32);
+        for (y, row) in self.data.iter().enumerate() {
+            let mut x = 0;
+            for run_pair in row.iter() {
+                if run_pair.color != BLACK_16 {
+                    builder.add_span(x, y as i64, run_pair.count as i64);
+                }
+                x += run_pair.count as i64;
+            }
+        }
+        builder.build()
+    }
+
+    /// Convert this gump back into its raw, storable form.
+    ///
+    /// The width and height aren't part of the data, and are stored in the index instead
+    pub fn serialize(&self) -> Vec<u8> {
+        let mut writer = vec![];
+        //Rows start straight after the offset table
+        let mut offset = self.data.len() as u32;
+        for row in self.data.iter() {
+            writer
+                .write_u32::<LittleEndian>(offset)
+                .expect(MEMWRITER_ERROR);
+            offset += row.len() as u32;
+        }
+        for run_pair in self.data.iter().flatten() {
+            writer
+                .write_u16::<LittleEndian>(run_pair.color)
+                .expect(MEMWRITER_ERROR);
+            writer
+                .write_u16::<LittleEndian>(run_pair.count)
+                .expect(MEMWRITER_ERROR);
+        }
+        writer
+    }
+}
+
+#[cfg(feature = "image")]
+impl Gump {
+    /// Convert this asset into a standarized image format
+    pub fn to_image(&self) -> Result<RgbaImage, ImageError> {
+        let mut buffer = RgbaImage::new(self.width as u32, self.height as u32);
+        for (y, row) in self.data.iter().enumerate() {
+            let mut x = 0;
+            for run_pair in row {
+                // Check for overflow
+                if x + run_pair.count > self.width {
+                    return Err(ImageError::Decoding(DecodingError::new(
+                        ImageFormatHint::Name("UO Gump".to_string()),
+                        ToImageError::PixelOutOfBounds {
+                            x: (x + run_pair.count) as i64,
+                            y: y as i64,
+                        },
+                    )));
+                }
+
+                // Pure black is Transparent in Gumps
+                if run_pair.color != BLACK_16 {
+                    let (r, g, b, a) = run_pair.color.to_rgba();
+                    for i in 0..run_pair.count {
+                        buffer.put_pixel(x as u32 + i as u32, y as u32, Rgba([r, g, b, a]));
+                    }
+                }
+                x += run_pair.count;
+            }
+        }
+        Ok(buffer)
+    }
+}
+
+#[cfg(feature = "image")]
+impl Gump {
+    /// Create a gump from an image, run-length encoding each row.
+    ///
+    /// Pixels with an alpha below 128 are treated as transparent, and stored as pure black. As
+    /// pure black is reserved for transparency, black pixels are stored as `NEAR_BLACK_16` instead
+    pub fn from_image(image: &RgbaImage) -> Result<Gump, FromImageError> {
+        let (width, height) = image.dimensions();
+        if width == 0 || width > u16::MAX as u32 || height == 0 || height > u16::MAX as u32 {
+            return Err(FromImageError::InvalidImageSize {
+                x: width,
+                y: height,
+            });
+        }
+
+        let mut data = vec![];
+        for y in 0..height {
+            let mut row: Vec<GumpPair> = vec![];
+            for x in 0..width {
+                let Rgba([r, g, b, a]) = *image.get_pixel(x, y);
+                let color = if a < 0x80 {
+                    BLACK_16
+                } else {
+                    match Color16::from_rgba(r, g, b, a) {
+                        BLACK_16 => NEAR_BLACK_16,
+                        color => color,
+                    }
+                };
+                match row.last_mut() {
+                    Some(run_pair) if run_pair.color == color => run_pair.count += 1,
+                    _ => row.push(GumpPair { color, count: 1 }),
+                }
+            }
+            data.push(row);
+        }
+
+        Ok(Gump {
+            width: width as u16,
+            height: height as u16,
+            data,
+        })
+    }
+}
+
+/// The nine gumps making up a resizable background, as drawn by the `resizepic` gump command.
+///
+/// Pieces are stored in rows from the top left: the four corners are drawn once, the edges are
+/// tiled along their side, and the centre is tiled to fill the remaining space
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct ResizePic {
+    pub pieces: [Gump; 9],
+}
+
+#[cfg(feature = "image")]
+impl ResizePic {
+    /// Draw the background at a given size.
+    ///
+    /// Pieces are laid out as the client does it, so backgrounds smaller than their corners
+    /// overlap rather than failing
+    pub fn render(&self, width: u32, height: u32) -> Result<RgbaImage, ImageError> {
+        let images = self
+            .pieces
+            .iter()
+            .map(Gump::to_image)
+            .collect::<Result<Vec<_>, _>>()?;
+        let [
+            top_left,
+            top,
+            top_right,
+            left,
+            center,
+            right,
+            bottom_left,
+            bottom,
+            bottom_right,
+        ] = &images[..]
+        else {
+            unreachable!("A resizepic always has nine pieces");
+        };
+        let (width, height) = (width as i64, height as i64);
+        let w = |image: &RgbaImage| image.width() as i64;
+        let h = |image: &RgbaImage| image.height() as i64;
+
+        let mut buffer = RgbaImage::new(width as u32, height as u32);
+        //The centre goes first, so the edges are drawn over it
+        tile_image(
+            &mut buffer,
+            center,
+            w(left),
+            h(top),
+            width - w(right),
+            height - h(bottom),
+        );
+        tile_image(
+            &mut buffer,
+            top,
+            w(top_left),
+            0,
+            width - w(top_right),
+            h(top),
+        );
+        tile_image(
+            &mut buffer,
+            bottom,
+            w(bottom_left),
+            height - h(bottom),
+            width - w(bottom_right),
+            height,
+        );
+        tile_image(
+            &mut buffer,
+            left,
+            0,
+            h(top_left),
+            w(left),
+            height - h(bottom_left),
+        );
+        tile_image(
+            &mut buffer,
+            right,
+            width - w(right),
+            h(top_right),
+            width,
+            height - h(bottom_right),
+        );
+        for (image, x, y) in [
+            (top_left, 0, 0),
+            (top_right, width - w(top_right), 0),
+            (bottom_left, 0, height - h(bottom_left)),
+            (
+                bottom_right,
+                width - w(bottom_right),
+                height - h(bottom_right),
+            ),
+        ] {
+            image::imageops::overlay(&mut buffer, image, x, y);
+        }
+        Ok(buffer)
+    }
+}
+
+/// Repeat an image across a rectangle, from its top left, clipping at the edges
+#[cfg(feature = "image")]
+fn tile_image(buffer: &mut RgbaImage, image: &RgbaImage, x1: i64, y1: i64, x2: i64, y2: i64) {
+    if image.width() == 0 || image.height() == 0 {
+        return;
+    }
+    for y in (y1..y2).step_by(image.height() as usize) {
+        for x in (x1..x2).step_by(image.width() as usize) {
+            let clip_width = (x2 - x).min(image.width() as i64) as u32;
+            let clip_height = (y2 - y).min(image.height() as i64) as u32;
+            let piece = image::imageops::crop_imm(image, 0, 0, clip_width, clip_height);
+            image::imageops::overlay(buffer, &*piece, x, y);
+        }
+    }
+}
+
+/// A struct to help read out Gump data
+#[derive(Debug)]
+pub struct GumpReader<T: Read + Seek> {
+    mul_reader: MulReader<T>,
+}
+
+impl GumpReader<File> {
+    /// Create a new GumpReader from an index and mul path
+    pub fn new(index_path: &Path, mul_path: &Path) -> MulReaderResult<GumpReader<File>> {
+        let mul_reader = MulReader::new(index_path, mul_path)?;
+        Ok(GumpReader { mul_reader })
+    }
+}
+
+impl<T: Read + Seek> GumpReader<T> {
+    /// Create a GumpReader from an existing mul reader
+    pub fn from_mul(reader: MulReader<T>) -> GumpReader<T> {
+        GumpReader { mul_reader: reader }
+    }
+
+    /// Read a single gump element
+    pub fn read(&mut self, index: u32) -> MulReaderResult<Gump> {
+        let raw = self.mul_reader.read(index)?;
+        let mut output = vec![];
+        let len = raw.data.len();
+
+        if len % 4 != 0 {
+            return Err(MulReaderError::UnexpectedSize {
+                found: len as u32,
+                expected: (len + (len % 4)) as u32,
+            });
+        }
+
+        let mut reader = Cursor::new(raw.data);
+        let mut row_offsets = vec![];
+        // Load all of our offsets. They're measured from the start of the file
+        for _i in 0..raw.opt1 {
+            row_offsets.push(reader.read_u32::<LittleEndian>()?);
+        }
+
+        // FIXME: The RLE stuff in here and in art should probably be abstracted
+        for (row_idx, offset) in row_offsets.iter().enumerate() {
+            let row_length = if row_idx == row_offsets.len() - 1 {
+                (len / 4) as u32 - offset
+            } else {
+                let next_row = row_offsets[row_idx + 1];
+                next_row - offset
+            };
+            reader.seek(SeekFrom::Start((*offset as u64) * 4))?;
+            let mut row = vec![];
+            for _i in 0..row_length {
+                let color = reader.read_u16::<LittleEndian>()?;
+                let count = reader.read_u16::<LittleEndian>()?;
+                row.push(GumpPair { color, count });
+            }
+            output.push(row);
+        }
+        Ok(Gump {
+            height: raw.opt1,
+            width: raw.opt2,
+            data: output,
+        })
+    }
+
+    /// Read the nine consecutive gumps of a resizable background, starting from its top left
+    /// corner
+    pub fn read_resizepic(&mut self, base_id: u32) -> MulReaderResult<ResizePic> {
+        let mut pieces = vec![];
+        for offset in 0..9 {
+            let id = base_id
+                .checked_add(offset)
+                .ok_or(MulReaderError::IndexOutOfBounds(base_id))?;
+            pieces.push(self.read(id)?);
+        }
+        let pieces = pieces.try_into().expect("Exactly nine pieces were read");
+        Ok(ResizePic { pieces })
+    }
+}
+
+/// A struct to help write Gump data
+#[derive(Debug)]
+pub struct GumpWriter<T: Write + Seek> {
+    mul_writer: MulWriter<T>,
+}
+
+impl GumpWriter<File> {
+    /// Create a new GumpWriter from an index and mul path
+    pub fn new(
+        index_path: &Path,
+        mul_path: &Path,
+        mode: MulWriterMode,
+    ) -> MulWriterResult<GumpWriter<File>> {
+        let mul_writer = MulWriter::new(index_path, mul_path, mode)?;
+        Ok(GumpWriter { mul_writer })
+    }
+}
+
+impl<T: Write + Seek> GumpWriter<T> {
+    /// Create a GumpWriter from an existing mul writer
+    pub fn from_mul(writer: MulWriter<T>) -> GumpWriter<T> {
+        GumpWriter { mul_writer: writer }
+    }
+
+    /// Write a single gump element, replacing any existing gump with that id
+    pub fn write(&mut self, index: u32, gump: &Gump) -> MulWriterResult<()> {
+        self.mul_writer.write(
+            index,
+            &gump.serialize(),
+            Some(gump.height),
+            Some(gump.width),
+        )
+    }
+
+    /// Remove a gump element from the index
+    pub fn delete(&mut self, index: u32) -> MulWriterResult<()> {
+        self.mul_writer.delete(index)
+    }
+}
@@ -0,0 +1,264 @@
+//! Methods for decoding and encoding the packets servers use to open gumps
+//!
+//! Packets are big-endian, and start with `|id:u8|length:u16|serial:u32|gump_id:u32|x:i32|y:i32|`.
+//!
+//! The classic packet, 0xB0, then has an ASCII layout and text lines stored as is:
+//!
+//! `|layout_length:u16|layout:[u8..layout_length]|line_count:u16|lines:[line..line_count]|`
+//!
+//! The compressed packet, 0xDD, stores the layout and text lines as zlib blocks:
+//!
+//! `|layout:block|line_count:u32|lines:block|`
+//!
+//! where a block is `|packed_length:u32|length:u32|data:[u8..packed_length - 4]|`, or a single
+//! `0` when it's empty. Decompressed, the lines use the same format as the classic packet.
+//!
+//! Each line is `|length:u16|text:[u16..length]|`, with the text stored as big-endian UTF-16.
+//! Compressed packets need the `zlib` feature.
+use crate::error::{
+    MEMWRITER_ERROR, MulReaderError, MulReaderResult, MulWriterError, MulWriterResult,
+};
+use crate::gump::layout::GumpLayout;
+use byteorder::{BigEndian, ReadBytesExt, WriteBytesExt};
+use std::io::{Cursor, Read, Write};
+
+/// The packet id of the classic gump packet
+pub const CLASSIC_GUMP: u8 = 0xB0;
+/// The packet id of the compressed gump packet
+pub const COMPRESSED_GUMP: u8 = 0xDD;
+
+const HEADER_SIZE: usize = 3;
+
+/// A gump sent by the server
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct GumpPacket {
+    /// The serial of the mobile or item the gump belongs to
+    pub serial: u32,
+    /// The server's id for this kind of gump, sent back in replies
+    pub gump_id: u32,
+    /// Where the gump is opened on screen
+    pub x: i32,
+    pub y: i32,
+    pub layout: GumpLayout,
+}
+
+impl GumpPacket {
+    /// Decode either gump packet, from its id onwards.
+    ///
+    /// The length in the header must fit within `data`, and anything after it is ignored
+    pub fn decode(data: &[u8]) -> MulReaderResult<GumpPacket> {
+        let mut reader = Cursor::new(data);
+        let id = reader.read_u8()?;
+        let length = reader.read_u16::<BigEndian>()? as usize;
+        if length < HEADER_SIZE || length > data.len() {
+            return Err(MulReaderError::UnexpectedSize {
+                found: data.len() as u32,
+                expected: length as u32,
+            });
+        }
+        let mut reader = Cursor::new(&data[HEADER_SIZE..length]);
+        let serial = reader.read_u32::<BigEndian>()?;
+        let gump_id = reader.read_u32::<BigEndian>()?;
+        let x = reader.read_i32::<BigEndian>()?;
+        let y = reader.read_i32::<BigEndian>()?;
+
+        let (layout, text) = match id {
+            CLASSIC_GUMP => {
+                let layout_length = reader.read_u16::<BigEndian>()?;
+                let mut layout = vec![0; layout_length as usize];
+                reader.read_exact(&mut layout)?;
+                let line_count = reader.read_u16::<BigEndian>()?;
+                (layout, read_lines(&mut reader, line_count as u32)?)
+            }
+            #[cfg(feature = "zlib")]
+            COMPRESSED_GUMP => {
+                let layout = read_block(&mut reader)?;
+                let line_count = reader.read_u32::<BigEndian>()?;
+                let lines = read_block(&mut reader)?;
+                (layout, read_lines(&mut Cursor::new(lines), line_count)?)
+            }
+            #[cfg(not(feature = "zlib"))]
+            COMPRESSED_GUMP => {
+                return Err(MulReaderError::FailedParse(
+                    "Compressed gump packets need the zlib feature".to_string(),
+                ));
+            }
+            _ => {
+                return Err(MulReaderError::FailedParse(format!(
+                    "Unsupported gump packet 0x{:02X}",
+                    id
+                )));
+            }
+        };
+
+        //Layouts are usually null terminated, and should only hold ASCII
+        let layout = String::from_utf8_lossy(&layout);
+        Ok(GumpPacket {
+            serial,
+            gump_id,
+            x,
+            y,
+            layout: GumpLayout::parse(layout.trim_end_matches('\0'), text)?,
+        })
+    }
+
+    fn write_header(&self, writer: &mut Vec<u8>, id: u8) {
+        writer.write_u8(id).expect(MEMWRITER_ERROR);
+        //The length is filled in once the packet is complete
+        writer.write_u16::<BigEndian>(0).expect(MEMWRITER_ERROR);
+        writer
+            .write_u32::<BigEndian>(self.serial)
+            .expect(MEMWRITER_ERROR);
+        writer
+            .write_u32::<BigEndian>(self.gump_id)
+            .expect(MEMWRITER_ERROR);
+        writer
+            .write_i32::<BigEndian>(self.x)
+            .expect(MEMWRITER_ERROR);
+        writer
+            .write_i32::<BigEndian>(self.y)
+            .expect(MEMWRITER_ERROR);
+    }
+
+    fn null_terminated_layout(&self) -> Vec<u8> {
+        let mut layout = self.layout.serialize().into_bytes();
+        layout.push(0);
+        layout
+    }
+
+    /// Encode this gump as a classic 0xB0 packet.
+    ///
+    /// Fails if the packet, or any part of it, is too large for its length field
+    pub fn encode_classic(&self) -> MulWriterResult<Vec<u8>> {
+        let mut writer = vec![];
+        self.write_header(&mut writer, CLASSIC_GUMP);
+        let layout = self.null_terminated_layout();
+        writer
+            .write_u16::<BigEndian>(fit_u16(layout.len())?)
+            .expect(MEMWRITER_ERROR);
+        writer.write_all(&layout).expect(MEMWRITER_ERROR);
+        writer
+            .write_u16::<BigEndian>(fit_u16(self.layout.text.len())?)
+            .expect(MEMWRITER_ERROR);
+        writer
+            .write_all(&serialize_lines(&self.layout.text)?)
+            .expect(MEMWRITER_ERROR);
+        finish_packet(writer)
+    }
+
+    /// Encode this gump as a compressed 0xDD packet.
+    ///
+    /// Fails if the packet, or any line, is too large for its length field
+    #[cfg(feature = "zlib")]
+    pub fn encode_compressed(&self) -> MulWriterResult<Vec<u8>> {
+        let mut writer = vec![];
+        self.write_header(&mut writer, COMPRESSED_GUMP);
+        write_block(&mut writer, &self.null_terminated_layout())?;
+        writer
+            .write_u32::<BigEndian>(self.layout.text.len() as u32)
+            .expect(MEMWRITER_ERROR);
+        write_block(&mut writer, &serialize_lines(&self.layout.text)?)?;
+        finish_packet(writer)
+    }
+}
+
+fn fit_u16(length: usize) -> MulWriterResult<u16> {
+    u16::try_from(length).map_err(|_| MulWriterError::UnexpectedSize {
+        found: length as u32,
+        expected: u16::MAX as u32,
+    })
+}
+
+/// Fill in the length of a finished packet
+fn finish_packet(mut packet: Vec<u8>) -> MulWriterResult<Vec<u8>> {
+    let length = fit_u16(packet.len())?;
+    packet[1..HEADER_SIZE].copy_from_slice(&length.to_be_bytes());
+    Ok(packet)
+}
+
+fn read_lines<T: Read>(reader: &mut T, line_count: u32) -> MulReaderResult<Vec<String>> {
+    let mut lines = vec![];
+    for _ in 0..line_count {
+        let length = reader.read_u16::<BigEndian>()?;
+        let mut units = vec![];
+        for _ in 0..length {
+            units.push(reader.read_u16::<BigEndian>()?);
+        }
+        lines.push(
+            String::from_utf16(&units)
+                .map_err(|e| MulReaderError::FailedParse(format!("Invalid text line: {}", e)))?,
+        );
+    }
+    Ok(lines)
+}
+
+fn serialize_lines(lines: &[String]) -> MulWriterResult<Vec<u8>> {
+    let mut writer = vec![];
+    for line in lines {
+        let units: Vec<u16> = line.encode_utf16().collect();
+        writer
+            .write_u16::<BigEndian>(fit_u16(units.len())?)
+            .expect(MEMWRITER_ERROR);
+        for unit in units {
+            writer.write_u16::<BigEndian>(unit).expect(MEMWRITER_ERROR);
+        }
+    }
+    Ok(writer)
+}
+
+/// Read and decompress a zlib block, which has to fit within what's left of the packet
+#[cfg(feature = "zlib")]
+fn read_block(reader: &mut Cursor<&[u8]>) -> MulReaderResult<Vec<u8>> {
+    use flate2::read::ZlibDecoder;
+
+    let packed_length = reader.read_u32::<BigEndian>()?;
+    if packed_length == 0 {
+        return Ok(vec![]);
+    }
+    let remaining = reader.get_ref().len() as u64 - reader.position();
+    if packed_length < 4 || packed_length as u64 > remaining {
+        return Err(MulReaderError::UnexpectedSize {
+            found: remaining as u32,
+            expected: packed_length,
+        });
+    }
+    let length = reader.read_u32::<BigEndian>()?;
+    let mut packed = vec![0; packed_length as usize - 4];
+    reader.read_exact(&mut packed)?;
+    //Read one byte past the stated length, so a block that inflates further is caught
+    //without decompressing all of it
+    let mut data = vec![];
+    ZlibDecoder::new(packed.as_slice())
+        .take(length as u64 + 1)
+        .read_to_end(&mut data)?;
+    if data.len() != length as usize {
+        return Err(MulReaderError::UnexpectedSize {
+            found: data.len() as u32,
+            expected: length,
+        });
+    }
+    Ok(data)
+}
+
+/// Compress and write a zlib block
+#[cfg(feature = "zlib")]
+fn write_block(writer: &mut Vec<u8>, data: &[u8]) -> MulWriterResult<()> {
+    use flate2::Compression;
+    use flate2::write::ZlibEncoder;
+
+    if data.is_empty() {
+        writer.write_u32::<BigEndian>(0).expect(MEMWRITER_ERROR);
+        return Ok(());
+    }
+    let mut encoder = ZlibEncoder::new(vec![], Compression::default());
+    encoder.write_all(data)?;
+    let packed = encoder.finish()?;
+    writer
+        .write_u32::<BigEndian>(packed.len() as u32 + 4)
+        .expect(MEMWRITER_ERROR);
+    writer
+        .write_u32::<BigEndian>(data.len() as u32)
+        .expect(MEMWRITER_ERROR);
+    writer.write_all(&packed).expect(MEMWRITER_ERROR);
+    Ok(())
+}
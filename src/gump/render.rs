@@ -0,0 +1,513 @@
+//! Methods for drawing server gump layouts without a client
+//!
+//! Each page is drawn onto an image just large enough to hold it, with the gump's origin at the
+//! top left. Anything placed at negative coordinates is clipped.
+//!
+//! This is a preview, rather than a faithful copy of the client:
+//!
+//! * Text uses the ASCII fonts from fonts.mul, as unicode fonts aren't read yet
+//! * HTML is reduced to plain text with line breaks, and wrapped to its box. Scrollbars aren't drawn
+//! * Localized strings can't be looked up, so `xmfhtmlgump` only draws its background
+//! * Hued statics are always fully hued, as tiledata isn't consulted for the partial hue flag
+use crate::art::{Art, ArtReader};
+use crate::bounds::Bounds;
+use crate::color::{BLACK_16, Color, NEAR_BLACK_16};
+use crate::error::{MulReaderError, MulReaderResult};
+use crate::font::{Character, Font};
+use crate::gump::layout::{ButtonAction, GumpCommand, GumpLayout};
+use crate::gump::{GumpReader, tile_image};
+use crate::hue::{Hue, HueReader};
+use image::imageops::{crop_imm, overlay};
+use image::{ImageError, Rgba, RgbaImage};
+use std::io::{Read, Seek};
+
+/// The font used for gump text, out of the fonts in fonts.mul
+pub const TEXT_FONT: usize = 1;
+/// The first gump of the resizable background drawn behind HTML boxes
+pub const HTML_BACKGROUND: u32 = 0x2486;
+/// The largest width or height of a rendered page, or of any element on it
+pub const MAX_RENDER_SIZE: u32 = 4096;
+
+/// What an interactive element does
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum ElementKind {
+    Button(ButtonAction),
+    CheckBox { switch_id: u32, checked: bool },
+    Radio { switch_id: u32, checked: bool },
+    TextEntry { entry_id: u32 },
+}
+
+/// An interactive element, and where it was drawn
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct GumpElement {
+    pub kind: ElementKind,
+    /// The area that can be clicked, clipped to the image
+    pub bounds: Bounds,
+}
+
+/// A drawn page of a gump
+#[derive(Debug, Clone)]
+pub struct RenderedGump {
+    pub image: RgbaImage,
+    /// Interactive elements, in the order they appear in the layout
+    pub elements: Vec<GumpElement>,
+}
+
+/// A single drawing step, positioned relative to the gump's origin
+enum DrawOp {
+    Image(i64, i64, RgbaImage),
+    /// Halve the opacity of everything drawn so far in a rectangle
+    Translucent(i64, i64, u32, u32),
+}
+
+/// Draws gump layouts using gumps, art, fonts and hues
+#[derive(Debug)]
+pub struct GumpRenderer<'a, G: Read + Seek, A: Read + Seek, H: Read + Seek> {
+    gumps: &'a mut GumpReader<G>,
+    art: &'a mut ArtReader<A>,
+    fonts: &'a [Font],
+    hues: Option<&'a mut HueReader<H>>,
+}
+
+impl<'a, G: Read + Seek, A: Read + Seek, H: Read + Seek> GumpRenderer<'a, G, A, H> {
+    /// Create a renderer. Fonts are as read by `FontReader::read_fonts`, and without a HueReader,
+    /// hues are ignored
+    pub fn new(
+        gumps: &'a mut GumpReader<G>,
+        art: &'a mut ArtReader<A>,
+        fonts: &'a [Font],
+        hues: Option<&'a mut HueReader<H>>,
+    ) -> GumpRenderer<'a, G, A, H> {
+        GumpRenderer {
+            gumps,
+            art,
+            fonts,
+            hues,
+        }
+    }
+
+    /// Look up an in-game hue, or None for unhued
+    fn read_hue(&mut self, hue: u16) -> MulReaderResult<Option<Hue>> {
+        //The top bits are flags rather than part of the hue number
+        let hue = hue & 0x3FFF;
+        match self.hues.as_mut() {
+            Some(reader) if hue != 0 => Ok(Some(reader.read_hue(hue as u32 - 1)?)),
+            _ => Ok(None),
+        }
+    }
+
+    fn gump_image(&mut self, gump_id: u32, hue: u16) -> MulReaderResult<RgbaImage> {
+        let mut gump = self.gumps.read(gump_id)?;
+        if let Some(hue) = self.read_hue(hue)? {
+            for run_pair in gump.data.iter_mut().flatten() {
+                run_pair.color = apply_hue(&hue, run_pair.color);
+            }
+        }
+        gump.to_image().map_err(image_error)
+    }
+
+    fn static_image(&mut self, item_id: u32, hue: u16) -> MulReaderResult<RgbaImage> {
+        let mut art = self.art.read_static(item_id)?;
+        if let Some(hue) = self.read_hue(hue)? {
+            for color in art
+                .rows
+                .iter_mut()
+                .flatten()
+                .flat_map(|run_pair| run_pair.run.iter_mut())
+            {
+                *color = apply_hue(&hue, *color);
+            }
+        }
+        Ok(art.to_image())
+    }
+
+    fn text_image(
+        &mut self,
+        text: &str,
+        hue: u16,
+        wrap_width: Option<u32>,
+    ) -> MulReaderResult<RgbaImage> {
+        let hue = self.read_hue(hue)?;
+        let Some(font) = self.fonts.get(TEXT_FONT).or(self.fonts.first()) else {
+            return Ok(RgbaImage::new(0, 0));
+        };
+        let lines = match wrap_width {
+            Some(width) => wrap_text(font, text, width),
+            None => text.lines().map(|line| line.to_string()).collect(),
+        };
+        Ok(draw_text(font, &lines, hue.as_ref()))
+    }
+
+    /// The box behind HTML text, either a resizable background or left transparent
+    fn html_background(
+        &mut self,
+        width: u32,
+        height: u32,
+        background: bool,
+    ) -> MulReaderResult<RgbaImage> {
+        check_size(width, height)?;
+        if background {
+            self.gumps
+                .read_resizepic(HTML_BACKGROUND)?
+                .render(width, height)
+                .map_err(image_error)
+        } else {
+            Ok(RgbaImage::new(width, height))
+        }
+    }
+
+    /// Draw a single page of a layout, along with page 0 beneath it.
+    ///
+    /// Fails if the page, or anything on it, is larger than `MAX_RENDER_SIZE`
+    pub fn render(&mut self, layout: &GumpLayout, page: u32) -> MulReaderResult<RenderedGump> {
+        let mut ops = vec![];
+        let mut elements = vec![];
+        for command in layout.page_commands(page) {
+            match *command {
+                GumpCommand::ResizePic {
+                    x,
+                    y,
+                    gump_id,
+                    width,
+                    height,
+                } => {
+                    check_size(width, height)?;
+                    let image = self
+                        .gumps
+                        .read_resizepic(gump_id)?
+                        .render(width, height)
+                        .map_err(image_error)?;
+                    ops.push(DrawOp::Image(x as i64, y as i64, image));
+                }
+                GumpCommand::GumpPic { x, y, gump_id, hue } => {
+                    ops.push(DrawOp::Image(
+                        x as i64,
+                        y as i64,
+                        self.gump_image(gump_id, hue)?,
+                    ));
+                }
+                GumpCommand::GumpPicTiled {
+                    x,
+                    y,
+                    width,
+                    height,
+                    gump_id,
+                } => {
+                    check_size(width, height)?;
+                    let mut image = RgbaImage::new(width, height);
+                    let piece = self.gump_image(gump_id, 0)?;
+                    tile_image(&mut image, &piece, 0, 0, width as i64, height as i64);
+                    ops.push(DrawOp::Image(x as i64, y as i64, image));
+                }
+                GumpCommand::TilePic { x, y, item_id } => {
+                    ops.push(DrawOp::Image(
+                        x as i64,
+                        y as i64,
+                        self.static_image(item_id, 0)?,
+                    ));
+                }
+                GumpCommand::TilePicHue { x, y, item_id, hue } => {
+                    ops.push(DrawOp::Image(
+                        x as i64,
+                        y as i64,
+                        self.static_image(item_id, hue)?,
+                    ));
+                }
+                GumpCommand::Text { x, y, hue, text_id } => {
+                    let image = self.text_image(layout.text_line(text_id), hue, None)?;
+                    ops.push(DrawOp::Image(x as i64, y as i64, image));
+                }
+                GumpCommand::CroppedText {
+                    x,
+                    y,
+                    width,
+                    height,
+                    hue,
+                    text_id,
+                } => {
+                    check_size(width, height)?;
+                    let image = self.text_image(layout.text_line(text_id), hue, None)?;
+                    ops.push(DrawOp::Image(
+                        x as i64,
+                        y as i64,
+                        crop_imm(&image, 0, 0, width, height).to_image(),
+                    ));
+                }
+                GumpCommand::Button {
+                    x,
+                    y,
+                    up_id,
+                    action,
+                    ..
+                } => {
+                    let image = self.gump_image(up_id, 0)?;
+                    elements.push((ElementKind::Button(action), x, y, image.dimensions()));
+                    ops.push(DrawOp::Image(x as i64, y as i64, image));
+                }
+                GumpCommand::CheckBox {
+                    x,
+                    y,
+                    off_id,
+                    on_id,
+                    checked,
+                    switch_id,
+                } => {
+                    let image = self.gump_image(if checked { on_id } else { off_id }, 0)?;
+                    let kind = ElementKind::CheckBox { switch_id, checked };
+                    elements.push((kind, x, y, image.dimensions()));
+                    ops.push(DrawOp::Image(x as i64, y as i64, image));
+                }
+                GumpCommand::Radio {
+                    x,
+                    y,
+                    off_id,
+                    on_id,
+                    checked,
+                    switch_id,
+                } => {
+                    let image = self.gump_image(if checked { on_id } else { off_id }, 0)?;
+                    let kind = ElementKind::Radio { switch_id, checked };
+                    elements.push((kind, x, y, image.dimensions()));
+                    ops.push(DrawOp::Image(x as i64, y as i64, image));
+                }
+                GumpCommand::TextEntry {
+                    x,
+                    y,
+                    width,
+                    height,
+                    hue,
+                    entry_id,
+                    text_id,
+                    ..
+                } => {
+                    check_size(width, height)?;
+                    let image = self.text_image(layout.text_line(text_id), hue, None)?;
+                    elements.push((ElementKind::TextEntry { entry_id }, x, y, (width, height)));
+                    //Text boxes don't draw anything themselves, but are still part of the gump
+                    let mut entry = RgbaImage::new(width, height);
+                    overlay(&mut entry, &image, 0, 0);
+                    ops.push(DrawOp::Image(x as i64, y as i64, entry));
+                }
+                GumpCommand::HtmlGump {
+                    x,
+                    y,
+                    width,
+                    height,
+                    text_id,
+                    background,
+                    ..
+                } => {
+                    let mut image = self.html_background(width, height, background)?;
+                    let text = html_to_text(layout.text_line(text_id));
+                    let text = self.text_image(&text, 0, Some(width))?;
+                    overlay(&mut image, &text, 0, 0);
+                    ops.push(DrawOp::Image(x as i64, y as i64, image));
+                }
+                GumpCommand::XmfHtmlGump {
+                    x,
+                    y,
+                    width,
+                    height,
+                    background,
+                    ..
+                } => {
+                    let image = self.html_background(width, height, background)?;
+                    ops.push(DrawOp::Image(x as i64, y as i64, image));
+                }
+                GumpCommand::CheckerTrans {
+                    x,
+                    y,
+                    width,
+                    height,
+                } => {
+                    ops.push(DrawOp::Translucent(x as i64, y as i64, width, height));
+                }
+                _ => {}
+            }
+        }
+
+        //Size the image to fit everything drawn, from the gump's origin
+        let mut width = 1;
+        let mut height = 1;
+        for op in ops.iter() {
+            let (x, y, w, h) = match op {
+                DrawOp::Image(x, y, image) => (*x, *y, image.width(), image.height()),
+                DrawOp::Translucent(..) => continue,
+            };
+            width = width.max((x + w as i64).clamp(0, u32::MAX as i64) as u32);
+            height = height.max((y + h as i64).clamp(0, u32::MAX as i64) as u32);
+        }
+
+        check_size(width, height)?;
+        let mut image = RgbaImage::new(width, height);
+        for op in ops {
+            match op {
+                DrawOp::Image(x, y, piece) => overlay(&mut image, &piece, x, y),
+                DrawOp::Translucent(x, y, w, h) => {
+                    if let Some(area) = clip(x, y, (w, h), width, height) {
+                        for py in area.y..area.y + area.height {
+                            for px in area.x..area.x + area.width {
+                                image.get_pixel_mut(px, py).0[3] /= 2;
+                            }
+                        }
+                    }
+                }
+            }
+        }
+
+        let elements = elements
+            .into_iter()
+            .filter_map(|(kind, x, y, size)| {
+                clip(x as i64, y as i64, size, width, height)
+                    .map(|bounds| GumpElement { kind, bounds })
+            })
+            .collect();
+        Ok(RenderedGump { image, elements })
+    }
+}
+
+/// Refuse sizes too large to draw, as layouts come from the server
+fn check_size(width: u32, height: u32) -> MulReaderResult<()> {
+    if width > MAX_RENDER_SIZE || height > MAX_RENDER_SIZE {
+        return Err(MulReaderError::FailedParse(format!(
+            "Size {}x{} is larger than the maximum of {}",
+            width, height, MAX_RENDER_SIZE
+        )));
+    }
+    Ok(())
+}
+
+fn image_error(error: ImageError) -> MulReaderError {
+    MulReaderError::FailedParse(format!("Failed to draw gump: {}", error))
+}
+
+/// Hue a gump or art color, keeping it from becoming transparent
+fn apply_hue(hue: &Hue, color: u16) -> u16 {
+    if color == BLACK_16 {
+        return color;
+    }
+    match hue.apply(color) {
+        BLACK_16 => NEAR_BLACK_16,
+        color => color,
+    }
+}
+
+/// Clip a rectangle to an image, returning None if nothing is left
+fn clip(x: i64, y: i64, size: (u32, u32), width: u32, height: u32) -> Option<Bounds> {
+    let x1 = x.clamp(0, width as i64);
+    let y1 = y.clamp(0, height as i64);
+    let x2 = (x + size.0 as i64).clamp(0, width as i64);
+    let y2 = (y + size.1 as i64).clamp(0, height as i64);
+    if x2 <= x1 || y2 <= y1 {
+        return None;
+    }
+    Some(Bounds {
+        x: x1 as u32,
+        y: y1 as u32,
+        width: (x2 - x1) as u32,
+        height: (y2 - y1) as u32,
+    })
+}
+
+/// Find the glyph for a character. Fonts skip the first 32 ASCII characters, and anything
+/// outside of the font is drawn as a question mark
+fn glyph(font: &Font, character: char) -> Option<&Character> {
+    let code = character as u32;
+    let code = if (32..256).contains(&code) {
+        code
+    } else {
+        '?' as u32
+    };
+    font.characters.get(code as usize - 32)
+}
+
+fn text_width(font: &Font, text: &str) -> u32 {
+    text.chars()
+        .filter_map(|character| glyph(font, character))
+        .map(|glyph| glyph.width as u32)
+        .sum()
+}
+
+/// Break text into lines no wider than `width`, at spaces where possible
+fn wrap_text(font: &Font, text: &str, width: u32) -> Vec<String> {
+    let mut lines = vec![];
+    for paragraph in text.lines() {
+        let mut line = String::new();
+        for word in paragraph.split(' ') {
+            let candidate = if line.is_empty() {
+                word.to_string()
+            } else {
+                format!("{} {}", line, word)
+            };
+            if text_width(font, &candidate) <= width || line.is_empty() {
+                line = candidate;
+            } else {
+                lines.push(std::mem::replace(&mut line, word.to_string()));
+            }
+        }
+        lines.push(line);
+    }
+    lines
+}
+
+/// Draw lines of text, with glyphs sitting on a shared baseline
+fn draw_text(font: &Font, lines: &[String], hue: Option<&Hue>) -> RgbaImage {
+    let line_height = font
+        .characters
+        .iter()
+        .map(|character| character.height as u32)
+        .max()
+        .unwrap_or(0);
+    let width = lines
+        .iter()
+        .map(|line| text_width(font, line))
+        .max()
+        .unwrap_or(0);
+    let mut buffer = RgbaImage::new(width, line_height * lines.len() as u32);
+    for (line_idx, line) in lines.iter().enumerate() {
+        let mut x = 0;
+        for glyph in line.chars().filter_map(|character| glyph(font, character)) {
+            let top = line_idx as u32 * line_height + line_height - glyph.height as u32;
+            for (idx, &pixel) in glyph.data.iter().enumerate() {
+                // Black is transparent in fonts
+                if pixel == BLACK_16 {
+                    continue;
+                }
+                let pixel = hue.map(|hue| apply_hue(hue, pixel)).unwrap_or(pixel);
+                let (r, g, b, a) = pixel.to_rgba();
+                buffer.put_pixel(
+                    x + (idx % glyph.width as usize) as u32,
+                    top + (idx / glyph.width as usize) as u32,
+                    Rgba([r, g, b, a]),
+                );
+            }
+            x += glyph.width as u32;
+        }
+    }
+    buffer
+}
+
+/// Reduce HTML to plain text, keeping line breaks
+fn html_to_text(html: &str) -> String {
+    let mut text = String::new();
+    let mut rest = html;
+    while let Some(start) = rest.find('<') {
+        text.push_str(&rest[..start]);
+        let Some(end) = rest[start..].find('>') else {
+            rest = &rest[start..];
+            break;
+        };
+        let tag = rest[start + 1..start + end].trim().to_ascii_lowercase();
+        let name = tag.split_whitespace().next().unwrap_or("");
+        if matches!(name, "br" | "br/" | "p" | "/p" | "/div") {
+            text.push('\n');
+        }
+        rest = &rest[start + end + 1..];
+    }
+    text.push_str(rest);
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&nbsp;", " ")
+        .replace("&amp;", "&")
+}
@@ -0,0 +1,279 @@
+//! Methods for hashing decoded art, and grouping identical or near-identical images
+//!
+//! Two hashes are taken of each image:
+//!
+//! * An exact hash, a 64-bit FNV-1a hash over the dimensions and every pixel, with transparent
+//!   pixels kept distinct from black. Equal images always have equal exact hashes, and unequal
+//!   images almost never do, but collisions are possible. Compare the pixels to be certain
+//! * A perceptual hash, a 64-bit difference hash over a downscaled greyscale copy. Small edits
+//!   such as recolored pixels or a shifted outline only flip a few bits, so images are compared by
+//!   the number of differing bits
+//!
+//! Hashes work directly from the stored data, so don't need the `image` feature.
+use crate::art::{Static, TILE_WIDTH, Tile};
+use crate::color::{BLACK_16, Color16};
+use crate::gump::Gump;
+use std::collections::HashMap;
+
+const FNV_OFFSET: u64 = 0xCBF29CE484222325;
+const FNV_PRIME: u64 = 0x100000001B3;
+const HASH_WIDTH: usize = 9;
+const HASH_HEIGHT: usize = 8;
+
+/// A decoded image, where None is a transparent pixel
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct PixelGrid {
+    pub width: u32,
+    pub height: u32,
+    /// Pixels in rows, from the top left
+    pub pixels: Vec<Option<Color16>>,
+}
+
+impl PixelGrid {
+    fn new(width: u32, height: u32) -> PixelGrid {
+        PixelGrid {
+            width,
+            height,
+            pixels: vec![None; width as usize * height as usize],
+        }
+    }
+
+    fn set(&mut self, x: u32, y: u32, color: Color16) {
+        if x < self.width && y < self.height {
+            self.pixels[(y * self.width + x) as usize] = Some(color);
+        }
+    }
+
+    fn exact_hash(&self) -> u64 {
+        let mut hash = FNV_OFFSET;
+        let mut feed = |byte: u8| {
+            hash ^= byte as u64;
+            hash = hash.wrapping_mul(FNV_PRIME);
+        };
+        for byte in self
+            .width
+            .to_le_bytes()
+            .iter()
+            .chain(&self.height.to_le_bytes())
+        {
+            feed(*byte);
+        }
+        for pixel in self.pixels.iter() {
+            //Transparent pixels are marked with the unused high bit, so they never match a color
+            let value = pixel.map(|color| color & 0x7FFF).unwrap_or(0x8000);
+            for byte in value.to_le_bytes() {
+                feed(byte);
+            }
+        }
+        hash
+    }
+
+    /// Average brightness of the pixels in a rectangle, with transparency counting as black
+    fn brightness(&self, x1: u32, y1: u32, x2: u32, y2: u32) -> u32 {
+        let mut total = 0;
+        for y in y1..y2 {
+            for x in x1..x2 {
+                if let Some(color) = self.pixels[(y * self.width + x) as usize] {
+                    let r = ((color >> 10) & 0x1F) as u32;
+                    let g = ((color >> 5) & 0x1F) as u32;
+                    let b = (color & 0x1F) as u32;
+                    total += r * 299 + g * 587 + b * 114;
+                }
+            }
+        }
+        total / ((x2 - x1) * (y2 - y1))
+    }
+
+    fn perceptual_hash(&self) -> u64 {
+        if self.width == 0 || self.height == 0 {
+            return 0;
+        }
+        //Each cell covers at least one pixel, so tiny images still hash
+        let span = |cell: usize, cells: usize, size: u32| {
+            let start = (cell as u64 * size as u64 / cells as u64) as u32;
+            let end = ((cell as u64 + 1) * size as u64).div_ceil(cells as u64) as u32;
+            (start.min(size - 1), end.max(start + 1).min(size))
+        };
+        let mut hash = 0;
+        for cell_y in 0..HASH_HEIGHT {
+            let (y1, y2) = span(cell_y, HASH_HEIGHT, self.height);
+            let row: Vec<u32> = (0..HASH_WIDTH)
+                .map(|cell_x| {
+                    let (x1, x2) = span(cell_x, HASH_WIDTH, self.width);
+                    self.brightness(x1, y1, x2, y2)
+                })
+                .collect();
+            for pair in row.windows(2) {
+                hash = (hash << 1) | (pair[0] < pair[1]) as u64;
+            }
+        }
+        hash
+    }
+}
+
+/// The hashes of a single image
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+pub struct ImageHash {
+    pub exact: u64,
+    pub perceptual: u64,
+}
+
+impl ImageHash {
+    /// The number of differing bits between two perceptual hashes. 0 is a close match, 64 is a
+    /// complete mismatch
+    pub fn distance(&self, other: &ImageHash) -> u32 {
+        (self.perceptual ^ other.perceptual).count_ones()
+    }
+}
+
+/// Assets which can be decoded and hashed
+pub trait ContentHash {
+    /// Decode the stored pixel data
+    fn pixel_grid(&self) -> PixelGrid;
+
+    /// Hash the decoded pixel data
+    fn content_hash(&self) -> ImageHash {
+        let grid = self.pixel_grid();
+        ImageHash {
+            exact: grid.exact_hash(),
+            perceptual: grid.perceptual_hash(),
+        }
+    }
+}
+
+impl ContentHash for Tile {
+    fn pixel_grid(&self) -> PixelGrid {
+        let mut grid = PixelGrid::new(TILE_WIDTH, TILE_WIDTH);
+        let mut pixels = self.image_data.iter();
+        for y in 0..TILE_WIDTH {
+            for x in 0..TILE_WIDTH {
+                if self.hit_test(x, y)
+                    && let Some(&color) = pixels.next()
+                {
+                    grid.set(x, y, color);
+                }
+            }
+        }
+        grid
+    }
+}
+
+impl ContentHash for Static {
+    fn pixel_grid(&self) -> PixelGrid {
+        let mut grid = PixelGrid::new(self.width as u32, self.height as u32);
+        for (y, row) in self.rows.iter().enumerate() {
+            let mut x = 0;
+            for run_pair in row.iter() {
+                x += run_pair.offset as u32;
+                for &color in run_pair.run.iter() {
+                    grid.set(x, y as u32, color);
+                    x += 1;
+                }
+            }
+        }
+        grid
+    }
+}
+
+impl ContentHash for Gump {
+    fn pixel_grid(&self) -> PixelGrid {
+        let mut grid = PixelGrid::new(self.width as u32, self.height as u32);
+        for (y, row) in self.data.iter().enumerate() {
+            let mut x = 0;
+            for run_pair in row.iter() {
+                // Pure black is Transparent in Gumps
+                if run_pair.color != BLACK_16 {
+                    for offset in 0..run_pair.count as u32 {
+                        grid.set(x + offset, y as u32, run_pair.color);
+                    }
+                }
+                x += run_pair.count as u32;
+            }
+        }
+        grid
+    }
+}
+
+/// Collects hashes of many images, and groups together the ones that match
+#[derive(Debug, Default, Clone)]
+pub struct DuplicateFinder {
+    hashes: Vec<(u32, ImageHash)>,
+}
+
+impl DuplicateFinder {
+    /// Create an empty finder
+    pub fn new() -> DuplicateFinder {
+        DuplicateFinder { hashes: vec![] }
+    }
+
+    /// Hash an asset, and remember it under the given id
+    pub fn add<A: ContentHash>(&mut self, id: u32, asset: &A) {
+        self.add_hash(id, asset.content_hash());
+    }
+
+    /// Remember a precomputed hash under the given id
+    pub fn add_hash(&mut self, id: u32, hash: ImageHash) {
+        self.hashes.push((id, hash));
+    }
+
+    /// Get the hash stored for an id
+    pub fn hash(&self, id: u32) -> Option<ImageHash> {
+        self.hashes
+            .iter()
+            .find(|(hashed_id, _)| *hashed_id == id)
+            .map(|(_, hash)| *hash)
+    }
+
+    /// Group ids with matching exact hashes, which are almost certainly identical images.
+    /// Only groups of two or more are returned, each sorted by id, and ordered by their first id
+    pub fn exact_groups(&self) -> Vec<Vec<u32>> {
+        let mut groups: HashMap<u64, Vec<u32>> = HashMap::new();
+        for (id, hash) in self.hashes.iter() {
+            groups.entry(hash.exact).or_default().push(*id);
+        }
+        finish_groups(groups.into_values())
+    }
+
+    /// Group ids whose perceptual hashes differ by at most `max_distance` bits.
+    ///
+    /// Matches are chained, so two images may share a group through a third that resembles both.
+    /// This compares every pair of images, so can be slow for very large sets
+    pub fn similar_groups(&self, max_distance: u32) -> Vec<Vec<u32>> {
+        let mut parents: Vec<usize> = (0..self.hashes.len()).collect();
+        fn root(parents: &mut [usize], mut idx: usize) -> usize {
+            while parents[idx] != idx {
+                parents[idx] = parents[parents[idx]];
+                idx = parents[idx];
+            }
+            idx
+        }
+
+        for a in 0..self.hashes.len() {
+            for b in (a + 1)..self.hashes.len() {
+                if self.hashes[a].1.distance(&self.hashes[b].1) <= max_distance {
+                    let (root_a, root_b) = (root(&mut parents, a), root(&mut parents, b));
+                    parents[root_b] = root_a;
+                }
+            }
+        }
+
+        let mut groups: HashMap<usize, Vec<u32>> = HashMap::new();
+        for idx in 0..self.hashes.len() {
+            let group = root(&mut parents, idx);
+            groups.entry(group).or_default().push(self.hashes[idx].0);
+        }
+        finish_groups(groups.into_values())
+    }
+}
+
+fn finish_groups<I: Iterator<Item = Vec<u32>>>(groups: I) -> Vec<Vec<u32>> {
+    let mut groups: Vec<Vec<u32>> = groups
+        .filter(|group| group.len() > 1)
+        .map(|mut group| {
+            group.sort();
+            group
+        })
+        .collect();
+    groups.sort();
+    groups
+}
@@ -0,0 +1,313 @@
+//! Methods for reading and writing hue data in hues.mul
+//!
+//! Hues are represented in a continuous, unindexed file as groups -
+//! `|header: u32|hues: [HueEntry..8]|`
+//!
+//! Individual HueEntries are defined as
+//! `|color_table:[u16..32]|table_start:u16|table_end:u16|name:[u8..20]|`
+//!
+use crate::color::Color16;
+use crate::error::{MEMWRITER_ERROR, MulWriterError, MulWriterResult};
+use crate::mul::MulWriterMode;
+use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
+use std::fs::{File, OpenOptions};
+use std::io::{Cursor, Read, Result, Seek, SeekFrom, Write};
+use std::path::Path;
+use std::str::from_utf8;
+
+pub mod palette;
+pub mod ramp;
+
+///An individual Hue.
+///
+///Hues act as a ramp of colors that can be used for palette swapping other art assets
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Hue {
+    ///32 color values
+    pub color_table: [Color16; 32],
+    ///The first hue value in the table
+    pub table_start: Color16,
+    ///The last hue value in the table
+    pub table_end: Color16,
+    ///A label for the hue
+    pub name: String,
+}
+
+impl Hue {
+    pub fn new(
+        color_table: [Color16; 32],
+        table_start: Color16,
+        table_end: Color16,
+        name: String,
+    ) -> Hue {
+        Hue {
+            color_table,
+            table_start,
+            table_end,
+            name,
+        }
+    }
+
+    ///Recolor a single pixel, using its brightness to pick a color from the ramp
+    pub fn apply(&self, color: Color16) -> Color16 {
+        //The red channel stands in for brightness, as hued art is drawn in greys
+        self.color_table[((color >> 10) & 0x1F) as usize]
+    }
+
+    ///Recolor a single pixel only if it's grey, as done for items with the partial hue flag
+    pub fn apply_partial(&self, color: Color16) -> Color16 {
+        let r = (color >> 10) & 0x1F;
+        let g = (color >> 5) & 0x1F;
+        let b = color & 0x1F;
+        if r == g && g == b {
+            self.apply(color)
+        } else {
+            color
+        }
+    }
+
+    ///Check that the name can be stored in a hue entry.
+    ///
+    ///Names must be ASCII, and no longer than 20 bytes
+    pub fn validate_name(&self) -> MulWriterResult<()> {
+        if !self.name.is_ascii() {
+            return Err(MulWriterError::NonAsciiName(self.name.clone()));
+        }
+        if self.name.len() > NAME_SIZE {
+            return Err(MulWriterError::NameTooLong {
+                name: self.name.clone(),
+                max: NAME_SIZE,
+            });
+        }
+        Ok(())
+    }
+
+    ///Convert a hue back into its canonical mul representation
+    ///
+    ///This will panic if the name is too long to fit - use `validate_name` to check beforehand
+    pub fn serialize(&self) -> Vec<u8> {
+        let mut writer = vec![];
+        for color in self.color_table.iter() {
+            writer
+                .write_u16::<LittleEndian>(*color)
+                .expect(MEMWRITER_ERROR);
+        }
+        writer
+            .write_u16::<LittleEndian>(self.table_start)
+            .expect(MEMWRITER_ERROR);
+        writer
+            .write_u16::<LittleEndian>(self.table_end)
+            .expect(MEMWRITER_ERROR);
+
+        writer
+            .write_all(self.name.as_bytes())
+            .expect(MEMWRITER_ERROR);
+        writer
+            .write_all(vec![0; NAME_SIZE - self.name.len()].as_slice())
+            .expect(MEMWRITER_ERROR);
+
+        assert_eq!(writer.len(), ENTRY_SIZE as usize);
+
+        writer
+    }
+}
+
+///A collection of 8 hues
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct HueGroup {
+    ///Unknown usage
+    pub header: u32,
+    pub entries: [Hue; 8],
+}
+
+impl HueGroup {
+    pub fn new(header: u32, entries: [Hue; 8]) -> HueGroup {
+        HueGroup { header, entries }
+    }
+
+    ///Convert a hue group back into its canonical mul representation
+    pub fn serialize(&self) -> Vec<u8> {
+        let mut writer = Cursor::new(vec![]);
+        writer
+            .write_u32::<LittleEndian>(self.header)
+            .expect(MEMWRITER_ERROR);
+        for hue in self.entries.iter() {
+            writer
+                .write_all(hue.serialize().as_slice())
+                .expect(MEMWRITER_ERROR);
+        }
+        writer.into_inner()
+    }
+
+    ///Check that every hue in the group can be serialized
+    pub fn validate_names(&self) -> MulWriterResult<()> {
+        for hue in self.entries.iter() {
+            hue.validate_name()?;
+        }
+        Ok(())
+    }
+}
+
+//Names are fixed-size, null-padded ASCII
+const NAME_SIZE: usize = 20;
+//A hue_entry is (32 * 2) + 2 + 2 + 20 bytes = 88 bytes
+const ENTRY_SIZE: u32 = 88;
+//Each group starts with an unknown u32
+const GROUP_HEADER_SIZE: u32 = 4;
+//8 entries to a group, plus a 4 byte header. 708 bytes.
+const GROUP_SIZE: u32 = (ENTRY_SIZE * 8) + GROUP_HEADER_SIZE;
+
+#[derive(Debug)]
+/// A struct to help read out Hue data
+pub struct HueReader<T: Read + Seek> {
+    data_reader: T,
+}
+
+impl HueReader<File> {
+    pub fn new(hues_path: &Path) -> Result<HueReader<File>> {
+        let data_reader = File::open(hues_path)?;
+
+        Ok(HueReader { data_reader })
+    }
+}
+
+impl<T: Read + Seek> HueReader<T> {
+    /// Create a HueReader from a file-like object
+    pub fn from_readable(data_reader: T) -> HueReader<T> {
+        HueReader { data_reader }
+    }
+
+    /// Read the given indexed group
+    pub fn read_hue_group(&mut self, id: u32) -> Result<HueGroup> {
+        self.data_reader
+            .seek(SeekFrom::Start((id * GROUP_SIZE) as u64))?;
+
+        let header = self.data_reader.read_u32::<LittleEndian>()?;
+
+        let entries: [Hue; 8] = [
+            self.read_hue_entry()?,
+            self.read_hue_entry()?,
+            self.read_hue_entry()?,
+            self.read_hue_entry()?,
+            self.read_hue_entry()?,
+            self.read_hue_entry()?,
+            self.read_hue_entry()?,
+            self.read_hue_entry()?,
+        ];
+
+        Ok(HueGroup { header, entries })
+    }
+
+    /// Read a single hue.
+    ///
+    /// As with `HueWriter::write_hue`, the id counts hues from the start of the file, so in-game
+    /// hue numbers need one subtracting
+    pub fn read_hue(&mut self, id: u32) -> Result<Hue> {
+        let group = (id / 8) as u64;
+        let entry = (id % 8) as u64;
+        self.data_reader.seek(SeekFrom::Start(
+            group * GROUP_SIZE as u64 + GROUP_HEADER_SIZE as u64 + entry * ENTRY_SIZE as u64,
+        ))?;
+        self.read_hue_entry()
+    }
+
+    fn read_hue_entry(&mut self) -> Result<Hue> {
+        let mut color_table = [0u16; 32];
+        for cell in &mut color_table {
+            *cell = self.data_reader.read_u16::<LittleEndian>()?;
+        }
+
+        let table_start = self.data_reader.read_u16::<LittleEndian>()?;
+        let table_end = self.data_reader.read_u16::<LittleEndian>()?;
+
+        let mut raw_name = [0; 20];
+        self.data_reader.read_exact(&mut raw_name)?;
+
+        //Slice it down into a normal string size
+        let trimmed_name: Vec<u8> = raw_name
+            .iter()
+            .take_while(|&element| *element != 0)
+            .cloned()
+            .collect();
+
+        let name = match from_utf8(trimmed_name.as_slice()) {
+            Ok(s) => {
+                if s.is_ascii() {
+                    s.to_string()
+                } else {
+                    "Error".to_string()
+                }
+            }
+            Err(_) => "Error".to_string(),
+        };
+
+        Ok(Hue::new(color_table, table_start, table_end, name))
+    }
+}
+
+/// A struct to help write Hue data, either building a new file or patching an existing one
+#[derive(Debug)]
+pub struct HueWriter<T: Write + Seek> {
+    data_writer: T,
+}
+
+impl HueWriter<File> {
+    /// Create a new HueWriter from a mul path.
+    ///
+    /// `MulWriterMode::Append` keeps the existing contents, so that hues can be replaced in place
+    pub fn new(hues_path: &Path, mode: MulWriterMode) -> MulWriterResult<HueWriter<File>> {
+        let data_writer = OpenOptions::new()
+            .write(true)
+            .create(true)
+            .truncate(match mode {
+                MulWriterMode::Append => false,
+                MulWriterMode::Truncate => true,
+            })
+            .open(hues_path)?;
+
+        Ok(HueWriter { data_writer })
+    }
+}
+
+impl<T: Write + Seek> HueWriter<T> {
+    /// Create a HueWriter from a file-like object
+    pub fn from_writable(data_writer: T) -> HueWriter<T> {
+        HueWriter { data_writer }
+    }
+
+    /// Write a sequence of groups from the start of the file
+    pub fn write_hue_groups(&mut self, groups: &[HueGroup]) -> MulWriterResult<()> {
+        for group in groups.iter() {
+            group.validate_names()?;
+        }
+        self.data_writer.seek(SeekFrom::Start(0))?;
+        for group in groups.iter() {
+            self.data_writer.write_all(&group.serialize())?;
+        }
+        Ok(())
+    }
+
+    /// Write a group at the given index, replacing whatever was there
+    pub fn write_hue_group(&mut self, id: u32, group: &HueGroup) -> MulWriterResult<()> {
+        group.validate_names()?;
+        self.data_writer
+            .seek(SeekFrom::Start(id as u64 * GROUP_SIZE as u64))?;
+        self.data_writer.write_all(&group.serialize())?;
+        Ok(())
+    }
+
+    /// Replace a single hue, leaving the rest of its group untouched.
+    ///
+    /// The id counts hues from the start of the file, so hue 9 is the second entry of group 1.
+    /// In-game hue numbers are offset by one, as 0 means "unhued"
+    pub fn write_hue(&mut self, id: u32, hue: &Hue) -> MulWriterResult<()> {
+        hue.validate_name()?;
+        let group = (id / 8) as u64;
+        let entry = (id % 8) as u64;
+        self.data_writer.seek(SeekFrom::Start(
+            group * GROUP_SIZE as u64 + GROUP_HEADER_SIZE as u64 + entry * ENTRY_SIZE as u64,
+        ))?;
+        self.data_writer.write_all(&hue.serialize())?;
+        Ok(())
+    }
+}
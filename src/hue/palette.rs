@@ -0,0 +1,264 @@
+//! Methods for moving hues in and out of common palette formats
+//!
+//! Each hue is treated as a run of 32 swatches, so a palette holding `n * 32` colors becomes `n`
+//! hues. Supported formats are
+//!
+//! * GIMP palettes (`.gpl`), a plain-text list of `r g b name` lines
+//! * Adobe color swatches (`.aco`), written as a version 1 block followed by a named version 2 block
+//! * Swatch strips, an image with one row of 32 square swatches per hue
+//!
+//! Colors are quantised down to 555 on import, so exporting and re-importing a hue is lossless.
+//! Imported hues take their `table_start` and `table_end` from the first and last colors in the ramp.
+use crate::color::{Color, Color16};
+use crate::error::{
+    MEMWRITER_ERROR, MulReaderError, MulReaderResult, MulWriterError, MulWriterResult,
+};
+use crate::hue::Hue;
+use byteorder::{BigEndian, ReadBytesExt, WriteBytesExt};
+use std::io::Cursor;
+
+#[cfg(feature = "image")]
+use image::{Rgba, RgbaImage};
+
+const RAMP_SIZE: usize = 32;
+const NAME_SIZE: usize = 20;
+const ACO_RGB: u16 = 0;
+
+fn hue_from_colors(colors: &[Color16], name: &str) -> Hue {
+    let mut color_table = [0; RAMP_SIZE];
+    color_table.copy_from_slice(colors);
+    //Hue names have to be short ASCII strings to be written back to a mul
+    let name: String = name
+        .chars()
+        .filter(|c| c.is_ascii())
+        .take(NAME_SIZE)
+        .collect();
+    Hue::new(
+        color_table,
+        color_table[0],
+        color_table[RAMP_SIZE - 1],
+        name,
+    )
+}
+
+fn hues_from_colors(colors: &[(Color16, String)]) -> MulReaderResult<Vec<Hue>> {
+    if colors.is_empty() || !colors.len().is_multiple_of(RAMP_SIZE) {
+        return Err(MulReaderError::FailedParse(format!(
+            "Palette has {} colors, expected a multiple of {}",
+            colors.len(),
+            RAMP_SIZE
+        )));
+    }
+    Ok(colors
+        .chunks(RAMP_SIZE)
+        .map(|chunk| {
+            let ramp: Vec<Color16> = chunk.iter().map(|(color, _)| *color).collect();
+            hue_from_colors(&ramp, &chunk[0].1)
+        })
+        .collect())
+}
+
+/// Export hues as a GIMP palette, with each swatch labelled with its hue's name
+pub fn export_gpl(name: &str, hues: &[Hue]) -> String {
+    let mut out = format!("GIMP Palette\nName: {}\nColumns: {}\n#\n", name, RAMP_SIZE);
+    for hue in hues {
+        for color in hue.color_table.iter() {
+            let (r, g, b, _a) = color.to_rgba();
+            out.push_str(&format!("{:3} {:3} {:3}\t{}\n", r, g, b, hue.name));
+        }
+    }
+    out
+}
+
+/// Import hues from a GIMP palette
+///
+/// Hue names are taken from the label of the first swatch in each ramp
+pub fn import_gpl(data: &str) -> MulReaderResult<Vec<Hue>> {
+    let mut lines = data.lines();
+    if lines.next().map(|line| line.trim()) != Some("GIMP Palette") {
+        return Err(MulReaderError::FailedParse(
+            "Missing GIMP Palette header".to_string(),
+        ));
+    }
+
+    let mut colors = vec![];
+    for (line_number, line) in lines.enumerate() {
+        let trimmed = line.trim();
+        if trimmed.is_empty()
+            || trimmed.starts_with('#')
+            || trimmed.starts_with("Name:")
+            || trimmed.starts_with("Columns:")
+        {
+            continue;
+        }
+        let parts: Vec<&str> = trimmed.split_whitespace().collect();
+        let channels: Vec<u8> = parts
+            .iter()
+            .take(3)
+            .filter_map(|value| value.parse::<u8>().ok())
+            .collect();
+        if channels.len() != 3 {
+            return Err(MulReaderError::FailedParse(format!(
+                "Invalid color on line {}: {}",
+                line_number + 2,
+                line
+            )));
+        }
+        let (r, g, b) = (channels[0], channels[1], channels[2]);
+        let label = parts[3..].join(" ");
+        colors.push((Color16::from_rgba(r, g, b, 255), label));
+    }
+    hues_from_colors(&colors)
+}
+
+/// Export hues as an Adobe color swatch file
+///
+/// Both the version 1 block and the version 2 block (which carries the hue names) are written.
+/// The swatch count is a u16, so this fails for more than 2047 hues
+pub fn export_aco(hues: &[Hue]) -> MulWriterResult<Vec<u8>> {
+    let count =
+        u16::try_from(hues.len() * RAMP_SIZE).map_err(|_| MulWriterError::UnexpectedSize {
+            found: hues.len() as u32,
+            expected: u16::MAX as u32 / RAMP_SIZE as u32,
+        })?;
+    let mut writer = vec![];
+    for version in 1..=2 {
+        writer
+            .write_u16::<BigEndian>(version)
+            .expect(MEMWRITER_ERROR);
+        writer.write_u16::<BigEndian>(count).expect(MEMWRITER_ERROR);
+        for hue in hues {
+            for color in hue.color_table.iter() {
+                let (r, g, b, _a) = color.to_rgba();
+                writer
+                    .write_u16::<BigEndian>(ACO_RGB)
+                    .expect(MEMWRITER_ERROR);
+                for channel in [r, g, b] {
+                    //Channels are stored as 0-65535
+                    writer
+                        .write_u16::<BigEndian>(channel as u16 * 257)
+                        .expect(MEMWRITER_ERROR);
+                }
+                writer.write_u16::<BigEndian>(0).expect(MEMWRITER_ERROR);
+
+                if version == 2 {
+                    //Names are length-prefixed, null-terminated UTF-16
+                    let name: Vec<u16> = hue.name.encode_utf16().collect();
+                    writer
+                        .write_u32::<BigEndian>(name.len() as u32 + 1)
+                        .expect(MEMWRITER_ERROR);
+                    for unit in name {
+                        writer.write_u16::<BigEndian>(unit).expect(MEMWRITER_ERROR);
+                    }
+                    writer.write_u16::<BigEndian>(0).expect(MEMWRITER_ERROR);
+                }
+            }
+        }
+    }
+    Ok(writer)
+}
+
+/// Import hues from an Adobe color swatch file
+///
+/// Only RGB swatches are supported. If a version 2 block is present, hue names are taken from it
+pub fn import_aco(data: &[u8]) -> MulReaderResult<Vec<Hue>> {
+    let mut reader = Cursor::new(data);
+    let mut colors = vec![];
+    let mut names = vec![];
+
+    while (reader.position() as usize) < data.len() {
+        let version = reader.read_u16::<BigEndian>()?;
+        if version != 1 && version != 2 {
+            return Err(MulReaderError::FailedParse(format!(
+                "Unknown swatch version {}",
+                version
+            )));
+        }
+        let count = reader.read_u16::<BigEndian>()?;
+        let mut block = vec![];
+        for index in 0..count {
+            let color_space = reader.read_u16::<BigEndian>()?;
+            if color_space != ACO_RGB {
+                return Err(MulReaderError::FailedParse(format!(
+                    "Swatch {} uses unsupported color space {}",
+                    index, color_space
+                )));
+            }
+            let r = (reader.read_u16::<BigEndian>()? / 257) as u8;
+            let g = (reader.read_u16::<BigEndian>()? / 257) as u8;
+            let b = (reader.read_u16::<BigEndian>()? / 257) as u8;
+            let _unused = reader.read_u16::<BigEndian>()?;
+            block.push(Color16::from_rgba(r, g, b, 255));
+
+            if version == 2 {
+                let length = reader.read_u32::<BigEndian>()?;
+                let mut name = vec![];
+                for _ in 0..length {
+                    name.push(reader.read_u16::<BigEndian>()?);
+                }
+                let name = String::from_utf16_lossy(&name);
+                names.push(name.trim_end_matches('\0').to_string());
+            }
+        }
+        //A version 2 block repeats the version 1 colors, so only keep one copy
+        colors = block;
+    }
+
+    names.resize(colors.len(), String::new());
+    hues_from_colors(&colors.into_iter().zip(names).collect::<Vec<_>>())
+}
+
+/// Render hues as a strip of square swatches, one row per hue
+#[cfg(feature = "image")]
+pub fn export_swatches(hues: &[Hue], swatch_size: u32) -> RgbaImage {
+    let mut buffer = RgbaImage::new(
+        RAMP_SIZE as u32 * swatch_size,
+        hues.len() as u32 * swatch_size,
+    );
+    for (y, hue) in hues.iter().enumerate() {
+        for (x, color) in hue.color_table.iter().enumerate() {
+            let (r, g, b, a) = color.to_rgba();
+            for offset_y in 0..swatch_size {
+                for offset_x in 0..swatch_size {
+                    buffer.put_pixel(
+                        x as u32 * swatch_size + offset_x,
+                        y as u32 * swatch_size + offset_y,
+                        Rgba([r, g, b, a]),
+                    );
+                }
+            }
+        }
+    }
+    buffer
+}
+
+/// Read hues back out of a swatch strip, sampling the center of each swatch
+///
+/// Swatch strips carry no names, so imported hues are unnamed
+#[cfg(feature = "image")]
+pub fn import_swatches(image: &RgbaImage, swatch_size: u32) -> MulReaderResult<Vec<Hue>> {
+    if swatch_size == 0
+        || image.width() != RAMP_SIZE as u32 * swatch_size
+        || !image.height().is_multiple_of(swatch_size)
+    {
+        return Err(MulReaderError::FailedParse(format!(
+            "Image of {}x{} is not a strip of {} swatches of size {}",
+            image.width(),
+            image.height(),
+            RAMP_SIZE,
+            swatch_size
+        )));
+    }
+    let mut colors = vec![];
+    for y in 0..(image.height() / swatch_size) {
+        for x in 0..RAMP_SIZE as u32 {
+            let pixel = image.get_pixel(
+                x * swatch_size + swatch_size / 2,
+                y * swatch_size + swatch_size / 2,
+            );
+            let [r, g, b, a] = pixel.0;
+            colors.push((Color16::from_rgba(r, g, b, a), String::new()));
+        }
+    }
+    hues_from_colors(&colors)
+}
@@ -0,0 +1,165 @@
+//! Methods for generating hue ramps from a handful of key colors
+//!
+//! A ramp runs from its darkest color at index 0 to its brightest at index 31. Anchor colors are
+//! spread evenly along the ramp, and the shade between them is blended in HSV space, taking the
+//! shortest way around the color wheel.
+//!
+//! By default the brightness of each entry is replaced by a curve between `min_brightness` and
+//! `max_brightness`, so a single anchor color is enough to produce a usable ramp.
+use crate::color::{Color, Color16};
+use crate::hue::Hue;
+
+const RAMP_SIZE: usize = 32;
+
+/// How brightness should change along the ramp
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum BrightnessCurve {
+    /// Keep the brightness blended from the anchor colors
+    Anchors,
+    /// Brightness increases evenly along the ramp
+    Linear,
+    /// Brightness follows `t^gamma`. Values above 1 keep more of the ramp dark
+    Gamma(f32),
+    /// Brightness eases in and out, giving more entries to mid-tones
+    Smooth,
+}
+
+impl BrightnessCurve {
+    fn apply(&self, t: f32) -> Option<f32> {
+        match *self {
+            BrightnessCurve::Anchors => None,
+            BrightnessCurve::Linear => Some(t),
+            BrightnessCurve::Gamma(gamma) => Some(t.powf(gamma)),
+            BrightnessCurve::Smooth => Some(t * t * (3.0 - 2.0 * t)),
+        }
+    }
+}
+
+/// Options to control ramp generation
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct RampOptions {
+    /// The shape of the brightness ramp
+    pub curve: BrightnessCurve,
+    /// Brightness of the first entry, from 0.0 to 1.0
+    pub min_brightness: f32,
+    /// Brightness of the last entry, from 0.0 to 1.0
+    pub max_brightness: f32,
+    /// A multiplier applied to the saturation of every entry
+    pub saturation: f32,
+}
+
+impl Default for RampOptions {
+    fn default() -> RampOptions {
+        RampOptions {
+            curve: BrightnessCurve::Linear,
+            min_brightness: 0.05,
+            max_brightness: 1.0,
+            saturation: 1.0,
+        }
+    }
+}
+
+fn to_hsv(r: u8, g: u8, b: u8) -> (f32, f32, f32) {
+    let (r, g, b) = (r as f32 / 255.0, g as f32 / 255.0, b as f32 / 255.0);
+    let max = r.max(g).max(b);
+    let min = r.min(g).min(b);
+    let delta = max - min;
+
+    let hue = if delta == 0.0 {
+        0.0
+    } else if max == r {
+        60.0 * ((g - b) / delta).rem_euclid(6.0)
+    } else if max == g {
+        60.0 * ((b - r) / delta + 2.0)
+    } else {
+        60.0 * ((r - g) / delta + 4.0)
+    };
+    let saturation = if max == 0.0 { 0.0 } else { delta / max };
+    (hue, saturation, max)
+}
+
+fn from_hsv(hue: f32, saturation: f32, value: f32) -> (u8, u8, u8) {
+    let chroma = value * saturation;
+    let sector = hue.rem_euclid(360.0) / 60.0;
+    let x = chroma * (1.0 - (sector.rem_euclid(2.0) - 1.0).abs());
+    let (r, g, b) = match sector as u32 {
+        0 => (chroma, x, 0.0),
+        1 => (x, chroma, 0.0),
+        2 => (0.0, chroma, x),
+        3 => (0.0, x, chroma),
+        4 => (x, 0.0, chroma),
+        _ => (chroma, 0.0, x),
+    };
+    let m = value - chroma;
+    let channel = |c: f32| ((c + m) * 255.0).round().clamp(0.0, 255.0) as u8;
+    (channel(r), channel(g), channel(b))
+}
+
+fn lerp(a: f32, b: f32, t: f32) -> f32 {
+    a + (b - a) * t
+}
+
+fn lerp_hue(a: f32, b: f32, t: f32) -> f32 {
+    //Go the short way around the wheel
+    let mut delta = (b - a).rem_euclid(360.0);
+    if delta > 180.0 {
+        delta -= 360.0;
+    }
+    a + delta * t
+}
+
+/// Generate a 32 color ramp from a list of anchor colors.
+///
+/// With no anchors, a greyscale ramp is produced
+pub fn generate_color_table(anchors: &[(u8, u8, u8)], options: &RampOptions) -> [Color16; 32] {
+    let anchors: Vec<(f32, f32, f32)> = if anchors.is_empty() {
+        vec![(0.0, 0.0, 0.0), (0.0, 0.0, 1.0)]
+    } else {
+        anchors.iter().map(|&(r, g, b)| to_hsv(r, g, b)).collect()
+    };
+
+    let mut color_table = [0; RAMP_SIZE];
+    for (idx, cell) in color_table.iter_mut().enumerate() {
+        let t = idx as f32 / (RAMP_SIZE - 1) as f32;
+
+        //Find the pair of anchors we're between
+        let position = t * (anchors.len() - 1) as f32;
+        let first = (position.floor() as usize).min(anchors.len() - 1);
+        let second = (first + 1).min(anchors.len() - 1);
+        let local_t = position - first as f32;
+        let (h1, s1, v1) = anchors[first];
+        let (h2, s2, v2) = anchors[second];
+
+        //Greys have no meaningful hue, so borrow the other anchor's
+        let (h1, h2) = match (s1 == 0.0, s2 == 0.0) {
+            (true, false) => (h2, h2),
+            (false, true) => (h1, h1),
+            _ => (h1, h2),
+        };
+
+        let hue = lerp_hue(h1, h2, local_t);
+        let saturation = (lerp(s1, s2, local_t) * options.saturation).clamp(0.0, 1.0);
+        let value = match options.curve.apply(t) {
+            Some(curve) => lerp(options.min_brightness, options.max_brightness, curve),
+            None => lerp(v1, v2, local_t),
+        }
+        .clamp(0.0, 1.0);
+
+        let (r, g, b) = from_hsv(hue, saturation, value);
+        *cell = Color16::from_rgba(r, g, b, 255);
+    }
+    color_table
+}
+
+/// Generate a named hue from a list of anchor colors.
+///
+/// `table_start` and `table_end` are set to the darkest and brightest entries
+pub fn generate_hue(name: &str, anchors: &[(u8, u8, u8)], options: &RampOptions) -> Hue {
+    let color_table = generate_color_table(anchors, options);
+    Hue::new(
+        color_table,
+        color_table[0],
+        color_table[RAMP_SIZE - 1],
+        name.to_string(),
+    )
+}
@@ -11,12 +11,16 @@ extern crate image;
 pub mod error;
 pub mod mul;
 
+pub mod bounds;
 pub mod color;
 
 pub mod anim;
 pub mod art;
+#[cfg(feature = "image")]
+pub mod atlas;
 pub mod font;
 pub mod gump;
+pub mod hash;
 pub mod hue;
 pub mod map;
 pub mod skill;
@@ -27,13 +31,29 @@ pub mod tiledata;
 mod tests {
     mod map;
     mod test_anim;
+    mod test_anim_canvas;
+    #[cfg(feature = "image")]
+    mod test_anim_compose;
+    #[cfg(feature = "image")]
+    mod test_anim_export;
     mod test_art;
+    #[cfg(feature = "image")]
+    mod test_atlas;
     mod test_color;
     mod test_font;
     mod test_gump;
+    mod test_gump_layout;
+    mod test_gump_packet;
+    #[cfg(feature = "image")]
+    mod test_gump_render;
+    mod test_hash;
     mod test_hue;
+    mod test_hue_palette;
+    mod test_hue_ramp;
     mod test_mul;
     mod test_skill;
     mod test_texmap;
     mod test_tiledata;
+    mod test_tiledata_interchange;
+    mod test_tiledata_table;
 }
@@ -1,14 +1,14 @@
-//! Methods for reading radar colors from radarcol.mul
+//! Methods for reading and writing radar colors in radarcol.mul
 //!
 //! This file is one of the simplest muls - a sequence of Color16s
 //!
-//! It's expected for the file to contain 65536 records.
-//! Each color matches a given tile in Art, and (offset by 16384 bytes) each static in there, too
+//! It's expected for the file to contain 65536 records, or more for clients with extended art.
+//! Each color matches a given tile in Art, and (offset by 16384 entries) each static in there, too
 use crate::color::Color16;
-use crate::error::MulReaderResult;
-use byteorder::{LittleEndian, ReadBytesExt};
-use std::fs::File;
-use std::io::{Read, Seek, SeekFrom};
+use crate::error::{MulReaderResult, MulWriterResult};
+use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
+use std::fs::{File, OpenOptions};
+use std::io::{Read, Seek, SeekFrom, Write};
 use std::path::Path;
 
 /// A struct to help read colors out of RadarCol
@@ -43,7 +43,7 @@ impl<T: Read + Seek> RadarColReader<T> {
 
     /// Read the color at a specific index
     pub fn read(&mut self, id: u32) -> MulReaderResult<Color16> {
-        self.data_reader.seek(SeekFrom::Start((id * 2) as u64))?;
+        self.data_reader.seek(SeekFrom::Start(id as u64 * 2))?;
         let data = self.data_reader.read_u16::<LittleEndian>()?;
         Ok(data)
     }
@@ -58,3 +58,36 @@ impl<T: Read + Seek> RadarColReader<T> {
         Ok(output)
     }
 }
+
+/// A struct to help write colors into RadarCol
+#[derive(Debug)]
+pub struct RadarColWriter<T: Write + Seek> {
+    data_writer: T,
+}
+
+impl RadarColWriter<File> {
+    /// Create a RadarColWriter from a path, keeping any existing colors
+    pub fn new(radar_col_path: &Path) -> MulWriterResult<RadarColWriter<File>> {
+        let data_writer = OpenOptions::new()
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(radar_col_path)?;
+
+        Ok(RadarColWriter { data_writer })
+    }
+}
+
+impl<T: Write + Seek> RadarColWriter<T> {
+    /// Create a RadarColWriter from an existing writable
+    pub fn from_writable(data_writer: T) -> RadarColWriter<T> {
+        RadarColWriter { data_writer }
+    }
+
+    /// Replace the color at a specific index
+    pub fn write(&mut self, id: u32, color: Color16) -> MulWriterResult<()> {
+        self.data_writer.seek(SeekFrom::Start(id as u64 * 2))?;
+        self.data_writer.write_u16::<LittleEndian>(color)?;
+        Ok(())
+    }
+}
@@ -1,3 +1,4 @@
+use crate::art::STATIC_OFFSET;
 use crate::error::{MulReaderError, MulReaderResult};
 use crate::mul::MulReader;
 use byteorder::{LittleEndian, ReadBytesExt};
@@ -45,8 +46,10 @@ pub struct StaticLocation {
 
 impl StaticLocation {
     /// Find the position in RadarCol that contains the color to render in the minimap
-    pub fn color_idx(&self) -> u16 {
-        self.object_id + 16384
+    ///
+    /// Statics are stored after the map tiles, so this can be beyond the range of a u16
+    pub fn color_idx(&self) -> u32 {
+        self.object_id as u32 + STATIC_OFFSET
     }
 }
 
@@ -1,4 +1,4 @@
-//! Methods for reading from standardized Mul and Idx files
+//! Methods for reading from and writing to standardized Mul and Idx files
 //!
 //! IDX files are defined as `|index:u32|size:u32|opt1:u16|opt2:u16|`
 //!
@@ -61,13 +61,36 @@ impl<T: Read + Seek> MulReader<T> {
         }
     }
 
+    /// Count the entries in the index, whether or not they're defined
+    pub fn entry_count(&mut self) -> MulReaderResult<u32> {
+        let length = self.idx_reader.seek(SeekFrom::End(0))?;
+        Ok((length / INDEX_SIZE as u64) as u32)
+    }
+
     /// Read a specific entry from the Mul.
     ///
     /// This method will return OffsetOutOfBounds if the index is marked invalid.
     pub fn read(&mut self, index: u32) -> MulReaderResult<MulRecord> {
+        self.read_part(index, 0, u32::MAX)
+    }
+
+    /// Read part of an entry from the Mul, starting `offset` bytes into it.
+    ///
+    /// Up to `length` bytes are read, stopping early at the end of the entry, so large entries
+    /// can be inspected without reading them whole. The returned record's `start` and `length`
+    /// describe the full entry.
+    ///
+    /// This method will return OffsetOutOfBounds if the index is marked invalid, or the offset is
+    /// past the end of the entry.
+    pub fn read_part(
+        &mut self,
+        index: u32,
+        offset: u32,
+        length: u32,
+    ) -> MulReaderResult<MulRecord> {
         //Wind the idx reader to the index position
         self.idx_reader
-            .seek(SeekFrom::Start((index * INDEX_SIZE) as u64))?;
+            .seek(SeekFrom::Start(index as u64 * INDEX_SIZE as u64))?;
 
         let start = self.idx_reader.read_u32::<LittleEndian>()?;
         //Check for empty cell
@@ -78,18 +101,22 @@ impl<T: Read + Seek> MulReader<T> {
             });
         }
 
-        let length = self.idx_reader.read_u32::<LittleEndian>()?;
-        let mut data = vec![0; length as usize];
+        let record_length = self.idx_reader.read_u32::<LittleEndian>()?;
+        if offset > record_length {
+            return Err(MulReaderError::OffsetOutOfBounds { index, offset });
+        }
+        let mut data = vec![0; length.min(record_length - offset) as usize];
         let opt1 = self.idx_reader.read_u16::<LittleEndian>()?;
         let opt2 = self.idx_reader.read_u16::<LittleEndian>()?;
 
-        self.data_reader.seek(SeekFrom::Start(start as u64))?;
+        self.data_reader
+            .seek(SeekFrom::Start(start as u64 + offset as u64))?;
         self.data_reader.read_exact(data.as_mut_slice())?;
 
         Ok(MulRecord {
             data,
             start,
-            length,
+            length: record_length,
             opt1,
             opt2,
         })
@@ -163,6 +190,56 @@ impl<T: Write + Seek> MulWriter<T> {
 
         Ok(())
     }
+
+    /// Write a value into a specific index, replacing any existing record there.
+    ///
+    /// The data is appended to the mul, so replaced records leave unused space behind.
+    /// If the index is past the end of the idx, the gap is filled with undefined records
+    pub fn write(
+        &mut self,
+        index: u32,
+        data: &[u8],
+        opt1: Option<u16>,
+        opt2: Option<u16>,
+    ) -> MulWriterResult<()> {
+        let mul_size = self.data_writer.seek(SeekFrom::End(0))?;
+        self.data_writer.write_all(data)?;
+
+        self.seek_index(index)?;
+        self.idx_writer.write_u32::<LittleEndian>(mul_size as u32)?;
+        self.idx_writer
+            .write_u32::<LittleEndian>(data.len() as u32)?;
+        self.idx_writer
+            .write_u16::<LittleEndian>(opt1.unwrap_or_default())?;
+        self.idx_writer
+            .write_u16::<LittleEndian>(opt2.unwrap_or_default())?;
+
+        Ok(())
+    }
+
+    /// Mark an index as undefined, so that it can no longer be read
+    pub fn delete(&mut self, index: u32) -> MulWriterResult<()> {
+        self.seek_index(index)?;
+        self.write_undefined()
+    }
+
+    fn write_undefined(&mut self) -> MulWriterResult<()> {
+        self.idx_writer.write_u32::<LittleEndian>(u32::MAX)?;
+        self.idx_writer.write_u32::<LittleEndian>(0)?;
+        self.idx_writer.write_u32::<LittleEndian>(0)?;
+        Ok(())
+    }
+
+    fn seek_index(&mut self, index: u32) -> MulWriterResult<()> {
+        let target = index as u64 * INDEX_SIZE as u64;
+        let idx_size = self.idx_writer.seek(SeekFrom::End(0))?;
+        //Pad out any missing records
+        for _ in (idx_size / INDEX_SIZE as u64)..(target / INDEX_SIZE as u64) {
+            self.write_undefined()?;
+        }
+        self.idx_writer.seek(SeekFrom::Start(target))?;
+        Ok(())
+    }
 }
 
 #[cfg(test)]
@@ -1,4 +1,4 @@
-use crate::map::radarcol::RadarColReader;
+use crate::map::radarcol::{RadarColReader, RadarColWriter};
 use byteorder::{LittleEndian, WriteBytesExt};
 use std::io::{Cursor, Result};
 
@@ -27,3 +27,17 @@ fn test_read_all() {
     let mut reader = RadarColReader::from_readable(data, len as u32);
     assert_eq!(reader.read_all().unwrap().len(), 65536);
 }
+
+#[test]
+fn test_write() {
+    let mut raw = raw_color_table().unwrap();
+    {
+        let mut writer = RadarColWriter::from_writable(Cursor::new(&mut raw));
+        writer.write(1, 0x1234).unwrap();
+    }
+    let len = raw.len();
+    let mut reader = RadarColReader::from_readable(Cursor::new(raw), len as u32);
+    assert_eq!(reader.read(0).unwrap(), 0);
+    assert_eq!(reader.read(1).unwrap(), 0x1234);
+    assert_eq!(reader.read(2).unwrap(), 2);
+}
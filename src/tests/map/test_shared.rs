@@ -56,3 +56,15 @@ fn test_read_block_statics() {
         }
     )
 }
+
+#[test]
+fn test_color_idx() {
+    let location = StaticLocation {
+        object_id: 0xFFFF,
+        x: 0,
+        y: 0,
+        altitude: 0,
+        checksum: 0,
+    };
+    assert_eq!(location.color_idx(), 0x13FFF);
+}
@@ -3,7 +3,12 @@ use byteorder::{LittleEndian, WriteBytesExt};
 use image::Pixel;
 use std::io::{Cursor, Result, Write};
 
-use crate::{anim::AnimReader, mul::tests::simple_from_vecs};
+#[cfg(feature = "image")]
+use crate::anim::{AnimGroup, quantise_palette};
+use crate::anim::{AnimReader, AnimWriter, Row, group_index};
+use crate::bounds::Bounds;
+use crate::mul::tests::simple_from_vecs;
+use crate::mul::{MulReader, MulWriter};
 
 fn raw_palette() -> Vec<u16> {
     (0..256)
@@ -85,3 +90,210 @@ fn test_to_frames() {
     assert_eq!(image.get_pixel(1, 2).channels(), white);
     assert_eq!(image.get_pixel(2, 2).channels(), transparent);
 }
+
+#[test]
+fn test_hit_test() {
+    let mul_reader = simple_from_vecs(vec![(raw_animation().unwrap(), 0, 0)]);
+    let mut reader = AnimReader::from_mul(mul_reader);
+    let mut frame = reader.read(0).unwrap().frames.remove(0);
+    assert!(!frame.hit_test(0, 0));
+    assert!(frame.hit_test(1, 0));
+    assert!(frame.hit_test(0, 1));
+    assert!(frame.hit_test(2, 1));
+    assert!(!frame.hit_test(2, 2));
+    assert!(!frame.hit_test(3, 1));
+    assert_eq!(
+        frame.bounds(),
+        Some(Bounds {
+            x: 0,
+            y: 0,
+            width: 3,
+            height: 3
+        })
+    );
+
+    //Drop the horizontal bar, leaving the two single pixels
+    frame.data.remove(1);
+    assert_eq!(
+        frame.bounds(),
+        Some(Bounds {
+            x: 1,
+            y: 0,
+            width: 1,
+            height: 3
+        })
+    );
+    frame.data.clear();
+    assert_eq!(frame.bounds(), None);
+}
+
+#[test]
+fn test_serialize() {
+    let raw = raw_animation().unwrap();
+    let mul_reader = simple_from_vecs(vec![(raw.clone(), 0, 0)]);
+    let mut reader = AnimReader::from_mul(mul_reader);
+    let anim = reader.read(0).unwrap();
+    assert_eq!(anim.serialize(), raw);
+    assert_eq!(anim.frames[0].data[0].serialize(), &raw[528..533]);
+}
+
+#[test]
+fn test_group_index() {
+    assert_eq!(group_index(0, 0, 0), Some(0));
+    assert_eq!(group_index(1, 2, 3), Some(110 + 13));
+    assert_eq!(group_index(199, 21, 4), Some(21999));
+    assert_eq!(group_index(200, 0, 0), Some(22000));
+    assert_eq!(group_index(400, 0, 0), Some(35000));
+    assert_eq!(group_index(401, 34, 4), Some(35000 + 175 + 174));
+    assert_eq!(group_index(0, 22, 0), None);
+    assert_eq!(group_index(200, 13, 0), None);
+    assert_eq!(group_index(400, 0, 5), None);
+    assert_eq!(group_index(u32::MAX, 0, 0), None);
+}
+
+#[test]
+fn test_write() {
+    let group = AnimReader::from_mul(simple_from_vecs(vec![(raw_animation().unwrap(), 0, 0)]))
+        .read(0)
+        .unwrap();
+    let mut second = group.clone();
+    second.frames.push(group.frames[0].clone());
+
+    let mut idx = vec![];
+    let mut mul = vec![];
+    {
+        let mut writer = AnimWriter::from_mul(MulWriter::from_writables(
+            Cursor::new(&mut idx),
+            Cursor::new(&mut mul),
+        ));
+        writer.write_body(1, 2, 3, &group).unwrap();
+        writer.write(4, &second).unwrap();
+        assert!(writer.write_body(0, 22, 0, &group).is_err());
+    }
+
+    let mut reader = AnimReader::from_mul(MulReader::from_readables(
+        Cursor::new(idx),
+        Cursor::new(mul),
+    ));
+    assert_eq!(reader.read_body(1, 2, 3).unwrap(), group);
+    let read_second = reader.read(4).unwrap();
+    assert_eq!(read_second.frame_count, 2);
+    assert_eq!(read_second.frames, second.frames);
+    assert!(reader.read(5).is_err());
+}
+
+#[test]
+fn test_read_header() {
+    let raw = raw_animation().unwrap();
+    let mut group = AnimReader::from_mul(simple_from_vecs(vec![(raw.clone(), 0, 0)]))
+        .read(0)
+        .unwrap();
+    group.frames.push(group.frames[0].clone());
+    group.frames[1].width = 4;
+    let mut blank = group.clone();
+    blank.palette = [0; 256];
+
+    let mut reader = AnimReader::from_mul(simple_from_vecs(vec![
+        (group.serialize(), 0, 0),
+        (blank.serialize(), 0, 0),
+    ]));
+    let header = reader.read_header(0).unwrap();
+    assert!(header.has_palette);
+    assert_eq!(header.frame_count, 2);
+    assert_eq!(header.frames.len(), 2);
+    //Both frames are the same size as the one in the raw animation
+    let frame_length = raw.len() as u32 - 520;
+    assert_eq!(header.frames[0].offset, 524);
+    assert_eq!(header.frames[0].length, frame_length);
+    assert_eq!(header.frames[1].offset, 524 + frame_length);
+    assert_eq!(header.frames[1].length, frame_length);
+    assert_eq!(
+        (
+            header.frames[1].image_center_x,
+            header.frames[1].image_center_y
+        ),
+        (1, 1)
+    );
+    assert_eq!((header.frames[1].width, header.frames[1].height), (4, 3));
+
+    assert_eq!(
+        reader.read_frame(0, &header.frames[1]).unwrap(),
+        group.frames[1]
+    );
+    assert_eq!(reader.read_palette(0).unwrap(), group.palette);
+    assert!(!reader.read_header(1).unwrap().has_palette);
+    assert!(reader.read_header(2).is_err());
+}
+
+#[test]
+fn test_row_new() {
+    let mul_reader = simple_from_vecs(vec![(raw_animation().unwrap(), 0, 0)]);
+    let mut reader = AnimReader::from_mul(mul_reader);
+    let frame = reader.read(0).unwrap().frames.remove(0);
+    for row in frame.data.iter() {
+        let x = row.x_offset(frame.image_center_x);
+        let y = row.y_offset(frame.image_center_y, frame.height as u32);
+        let rebuilt = Row::new(
+            x,
+            y,
+            frame.image_center_x,
+            frame.image_center_y,
+            frame.height as u32,
+            row.image_data.clone(),
+        )
+        .unwrap();
+        assert_eq!(&rebuilt, row);
+    }
+    assert!(Row::new(600, 0, 0, 0, 0, vec![]).is_none());
+    assert!(Row::new(0, 0, 0, 0, 0, vec![0; 0x1000]).is_none());
+}
+
+#[cfg(feature = "image")]
+#[test]
+fn test_from_images() {
+    let white = image::Rgba([255, 255, 255, 255]);
+    let mut cross = image::RgbaImage::new(3, 3);
+    for (x, y) in [(1, 0), (0, 1), (1, 1), (2, 1), (1, 2)] {
+        cross.put_pixel(x, y, white);
+    }
+    let group = AnimGroup::from_images(&[cross.clone()], (1, 4)).unwrap();
+    assert_eq!(group.frame_count, 1);
+    assert_eq!(group.palette[0], 0x7FFF);
+    let frame = &group.frames[0];
+    assert_eq!((frame.image_center_x, frame.image_center_y), (1, 1));
+    assert_eq!(frame.data.len(), 3);
+    assert_eq!(frame.data[1].image_data, vec![0, 0, 0]);
+
+    //Rendering the imported group gives back the original image
+    let frames = group.to_frames().collect_frames().unwrap();
+    assert_eq!(frames[0].buffer(), &cross);
+
+    assert!(AnimGroup::from_images(&[cross], (1000, 0)).is_err());
+}
+
+#[cfg(feature = "image")]
+#[test]
+fn test_quantise_palette() {
+    //A gradient with 1024 distinct colors
+    let mut image = image::RgbaImage::new(32, 32);
+    for (x, y, pixel) in image.enumerate_pixels_mut() {
+        *pixel = image::Rgba([(x * 8) as u8, (y * 8) as u8, 128, 255]);
+    }
+    let palette = quantise_palette(&[image.clone()]);
+    let mut unique = palette.to_vec();
+    unique.sort();
+    unique.dedup();
+    assert_eq!(unique.len(), 256);
+
+    let group = AnimGroup::from_images_with_palette(&[image], (0, 32), &palette).unwrap();
+    for row in group.frames[0].data.iter() {
+        let y = row.y_offset(0, 32) as u16;
+        for (x, index) in row.image_data.iter().enumerate() {
+            let color = palette[*index as usize];
+            let red = (color >> 10) & 0x1F;
+            let green = (color >> 5) & 0x1F;
+            assert!(red.abs_diff(x as u16) <= 2);
+            assert!(green.abs_diff(y) <= 2);
+        }
+    }
+}
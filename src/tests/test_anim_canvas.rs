@@ -0,0 +1,136 @@
+use crate::anim::canvas::AnimCanvas;
+#[cfg(feature = "image")]
+use crate::anim::export::ExportOptions;
+use crate::anim::{AnimFrame, AnimGroup, AnimWriter, Row};
+use crate::mul::{MulReader, MulWriter};
+use std::io::Cursor;
+
+pub fn example_frame(
+    image_center_x: i16,
+    image_center_y: i16,
+    width: u16,
+    height: u16,
+) -> AnimFrame {
+    //Fill the bottom row, so the anchor sits just below the frame's feet
+    let row = Row::new(
+        0,
+        height as i32 - 1,
+        image_center_x,
+        image_center_y,
+        height as u32,
+        vec![1; width as usize],
+    )
+    .unwrap();
+    AnimFrame {
+        image_center_x,
+        image_center_y,
+        width,
+        height,
+        data: vec![row],
+    }
+}
+
+pub fn example_group(frames: Vec<AnimFrame>) -> AnimGroup {
+    let mut palette = [0; 256];
+    palette[1] = 0x7FFF;
+    AnimGroup {
+        palette,
+        frame_count: frames.len() as u32,
+        frames,
+    }
+}
+
+#[test]
+fn test_for_frames() {
+    let frames = [example_frame(2, 0, 4, 2), example_frame(0, 1, 2, 5)];
+    let canvas = AnimCanvas::for_frames(&frames);
+    assert_eq!(
+        canvas,
+        AnimCanvas {
+            width: 4,
+            height: 6,
+            anchor_x: 2,
+            anchor_y: 6,
+        }
+    );
+    assert_eq!(canvas.frame_origin(&frames[0]), (0, 4));
+    assert_eq!(canvas.frame_origin(&frames[1]), (2, 0));
+
+    let empty = AnimCanvas::for_frames(&[example_frame(0, 0, 0, 0)]);
+    assert_eq!((empty.width, empty.height), (1, 1));
+}
+
+#[test]
+fn test_union() {
+    //A frame reaching left of the anchor, and one reaching right and below
+    let left = AnimCanvas::for_frames(&[example_frame(5, 0, 3, 3)]);
+    let right = AnimCanvas::for_frames(&[example_frame(-1, -2, 4, 3)]);
+    let both = left.union(&right);
+    assert_eq!(
+        both,
+        AnimCanvas {
+            width: 10,
+            height: 5,
+            anchor_x: 5,
+            anchor_y: 3,
+        }
+    );
+    assert_eq!(both, right.union(&left));
+
+    let groups = [
+        example_group(vec![example_frame(5, 0, 3, 3)]),
+        example_group(vec![example_frame(-1, -2, 4, 3)]),
+    ];
+    assert_eq!(AnimCanvas::for_groups(&groups), both);
+}
+
+#[cfg(feature = "image")]
+#[test]
+fn test_aligned_frames() {
+    let north = example_group(vec![example_frame(5, 0, 3, 3)]);
+    let south = example_group(vec![example_frame(-1, -2, 4, 3)]);
+    let canvas = AnimCanvas::for_groups([&north, &south]);
+
+    let north_frames = north.to_aligned_frames(&canvas);
+    let south_frames = south.to_aligned_frames(&canvas);
+    assert_eq!(north_frames[0].dimensions(), (10, 5));
+    assert_eq!(south_frames[0].dimensions(), (10, 5));
+    //North's bottom row ends two pixels left of the anchor, south's starts one pixel right
+    assert_eq!(north_frames[0].get_pixel(2, 2).0, [255, 255, 255, 255]);
+    assert_eq!(north_frames[0].get_pixel(3, 2).0, [0, 0, 0, 0]);
+    assert_eq!(south_frames[0].get_pixel(6, 4).0, [255, 255, 255, 255]);
+    assert_eq!(south_frames[0].get_pixel(5, 4).0, [0, 0, 0, 0]);
+
+    //Exports can share the canvas too
+    let options = ExportOptions {
+        canvas: Some(canvas),
+        ..ExportOptions::default()
+    };
+    let timed = north.to_timed_frames(&options);
+    assert_eq!(timed[0].buffer(), &north_frames[0]);
+}
+
+#[test]
+fn test_read_directions() {
+    let mut idx = vec![];
+    let mut mul = vec![];
+    {
+        let mut writer = AnimWriter::from_mul(MulWriter::from_writables(
+            Cursor::new(&mut idx),
+            Cursor::new(&mut mul),
+        ));
+        for direction in 0..5 {
+            let group = example_group(vec![example_frame(0, 0, direction as u16 + 1, 1)]);
+            writer.write_body(3, 1, direction, &group).unwrap();
+        }
+    }
+    let mut reader = crate::anim::AnimReader::from_mul(MulReader::from_readables(
+        Cursor::new(idx),
+        Cursor::new(mul),
+    ));
+    let directions = reader.read_directions(3, 1).unwrap();
+    assert_eq!(directions.len(), 5);
+    assert_eq!(directions[4].frames[0].width, 5);
+    assert_eq!(AnimCanvas::for_groups(&directions).width, 5);
+    assert!(reader.read_directions(3, 0).is_err());
+}
@@ -0,0 +1,261 @@
+use crate::anim::compose::{
+    Compositor, Equipment, MobileAppearance, Mount, file_direction, layer_order,
+};
+use crate::anim::{AnimFrame, AnimGroup, AnimReader, AnimWriter, Row};
+use crate::hue::{Hue, HueReader, HueWriter};
+use crate::mul::{MulReader, MulWriter};
+use crate::tiledata::{
+    Flags, Layer, MapTileData, MapTileGroup, StaticTileData, StaticTileGroup, TileDataLayout,
+    TileDataReader, TileDataWriter,
+};
+use std::io::Cursor;
+
+const GREY: u16 = 0x4210;
+const RED: u16 = 0x7C00;
+const BLUE: u16 = 0x001F;
+
+/// A solid block, anchored below its bottom middle
+fn block(width: u16, height: u16, color: u16) -> AnimGroup {
+    let image_center_x = (width / 2) as i16;
+    let data = (0..height as i32)
+        .map(|y| {
+            Row::new(
+                0,
+                y,
+                image_center_x,
+                0,
+                height as u32,
+                vec![1; width as usize],
+            )
+            .unwrap()
+        })
+        .collect();
+    let mut palette = [0; 256];
+    palette[1] = color;
+    AnimGroup {
+        palette,
+        frame_count: 1,
+        frames: vec![AnimFrame {
+            image_center_x,
+            image_center_y: 0,
+            width,
+            height,
+            data,
+        }],
+    }
+}
+
+fn wearable(name: &str, layer: Layer, anim_id: u16, flags: Flags) -> StaticTileData {
+    StaticTileData {
+        flags: flags | Flags::WearableFlag,
+        weight: 1,
+        quality_layer_light_id: layer.id(),
+        unknown: 0,
+        unknown1: 0,
+        quantity_weapon_class_armor_class: 0,
+        anim_id,
+        unknown2: 0,
+        hue: 0,
+        unknown3: 0,
+        height_capacity: 0,
+        name: name.to_string(),
+        raw_name: [0; 20],
+    }
+}
+
+struct Fixtures {
+    anims: AnimReader<Cursor<Vec<u8>>>,
+    tiledata: TileDataReader<Cursor<Vec<u8>>>,
+    hues: HueReader<Cursor<Vec<u8>>>,
+}
+
+fn fixtures() -> Fixtures {
+    let mut idx = vec![];
+    let mut mul = vec![];
+    {
+        let mut writer = AnimWriter::from_mul(MulWriter::from_writables(
+            Cursor::new(&mut idx),
+            Cursor::new(&mut mul),
+        ));
+        for direction in [1, 2, 3] {
+            writer
+                .write_body(400, 0, direction, &block(3, 3, GREY))
+                .unwrap();
+            writer
+                .write_body(500, 0, direction, &block(3, 3, RED))
+                .unwrap();
+            writer
+                .write_body(501, 0, direction, &block(1, 1, GREY))
+                .unwrap();
+            writer
+                .write_body(200, 2, direction, &block(5, 1, GREY))
+                .unwrap();
+        }
+    }
+
+    let mut tiledata = vec![];
+    {
+        let map_group = MapTileGroup {
+            header: 0,
+            entries: vec![
+                MapTileData {
+                    flags: Flags::empty(),
+                    texture_id: 0,
+                    name: String::new(),
+                    raw_name: [0; 20],
+                };
+                32
+            ],
+        };
+        let mut entries = vec![
+            wearable("cloak", Layer::Cloak, 500, Flags::empty()),
+            wearable("shirt", Layer::Shirt, 501, Flags::PartialHueFlag),
+            wearable("ring", Layer::Ring, 999, Flags::empty()),
+        ];
+        entries.resize(32, wearable("", Layer::Ring, 0, Flags::empty()));
+        let static_group = StaticTileGroup { header: 0, entries };
+        let mut writer =
+            TileDataWriter::from_writable(Cursor::new(&mut tiledata), TileDataLayout::Classic);
+        writer
+            .write_all(&vec![map_group; 512], &[static_group])
+            .unwrap();
+    }
+
+    let mut hues = vec![];
+    HueWriter::from_writable(Cursor::new(&mut hues))
+        .write_hue(0, &Hue::new([BLUE; 32], BLUE, BLUE, "blue".to_string()))
+        .unwrap();
+
+    Fixtures {
+        anims: AnimReader::from_mul(MulReader::from_readables(
+            Cursor::new(idx),
+            Cursor::new(mul),
+        )),
+        tiledata: TileDataReader::from_readable_with_layout(
+            Cursor::new(tiledata),
+            TileDataLayout::Classic,
+        )
+        .unwrap(),
+        hues: HueReader::from_readable(Cursor::new(hues)),
+    }
+}
+
+fn color(pixel: &image::Rgba<u8>) -> [u8; 4] {
+    pixel.0
+}
+
+#[test]
+fn test_directions() {
+    assert_eq!(file_direction(3), (0, false));
+    //Mirroring swaps left and right on screen
+    assert_eq!(file_direction(0), (3, true));
+    assert_eq!(file_direction(6), (3, false));
+    assert_eq!(file_direction(2), (1, true));
+    assert_eq!(file_direction(4), (1, false));
+    assert_eq!(file_direction(1), (2, true));
+    assert_eq!(file_direction(5), (2, false));
+    assert_eq!(file_direction(7), (4, false));
+    assert_eq!(layer_order(4).first(), Some(&Layer::Backpack));
+    assert_eq!(layer_order(1).first(), Some(&Layer::Backpack));
+    for direction in [6, 7, 0] {
+        assert_eq!(layer_order(direction).last(), Some(&Layer::Helm));
+    }
+}
+
+#[test]
+fn test_compose_layers() {
+    let mut fixtures = fixtures();
+    let mut compositor = Compositor::new(
+        &mut fixtures.anims,
+        &mut fixtures.tiledata,
+        Some(&mut fixtures.hues),
+    );
+    let appearance = MobileAppearance {
+        body: 400,
+        hue: 0,
+        equipment: vec![
+            Equipment { item_id: 0, hue: 0 },
+            Equipment { item_id: 1, hue: 1 },
+            //The ring has no animation, so is skipped
+            Equipment { item_id: 2, hue: 0 },
+        ],
+        mount: None,
+    };
+
+    //Facing the viewer, the shirt is drawn over the cloak, and hued blue
+    let front = compositor.compose(&appearance, 0, 4).unwrap();
+    assert_eq!(front.frames.len(), 1);
+    assert_eq!(front.frames[0].dimensions(), (3, 3));
+    assert_eq!(color(front.frames[0].get_pixel(1, 2)), [0, 0, 255, 255]);
+    assert_eq!(color(front.frames[0].get_pixel(0, 0)), [255, 0, 0, 255]);
+
+    //Facing away, the cloak covers it
+    let back = compositor.compose(&appearance, 0, 6).unwrap();
+    assert_eq!(color(back.frames[0].get_pixel(1, 2)), [255, 0, 0, 255]);
+
+    assert!(compositor.compose(&appearance, 1, 4).is_err());
+}
+
+#[test]
+fn test_compose_hue() {
+    let mut fixtures = fixtures();
+    let appearance = MobileAppearance {
+        body: 400,
+        hue: 1,
+        equipment: vec![],
+        mount: None,
+    };
+    let mut compositor = Compositor::new(
+        &mut fixtures.anims,
+        &mut fixtures.tiledata,
+        Some(&mut fixtures.hues),
+    );
+    let hued = compositor.compose(&appearance, 0, 4).unwrap();
+    assert_eq!(color(hued.frames[0].get_pixel(0, 0)), [0, 0, 255, 255]);
+
+    let mut compositor = Compositor::new(
+        &mut fixtures.anims,
+        &mut fixtures.tiledata,
+        None::<&mut HueReader<Cursor<Vec<u8>>>>,
+    );
+    let unhued = compositor.compose(&appearance, 0, 4).unwrap();
+    assert_eq!(
+        color(unhued.frames[0].get_pixel(0, 0)),
+        [131, 131, 131, 255]
+    );
+}
+
+#[test]
+fn test_compose_mount() {
+    let mut fixtures = fixtures();
+    let mut compositor = Compositor::new(
+        &mut fixtures.anims,
+        &mut fixtures.tiledata,
+        Some(&mut fixtures.hues),
+    );
+    let appearance = MobileAppearance {
+        body: 400,
+        hue: 0,
+        equipment: vec![Equipment { item_id: 0, hue: 0 }],
+        mount: Some(Mount {
+            body: 200,
+            hue: 1,
+            action: 2,
+            rider_offset_x: 1,
+            rider_offset_y: -1,
+        }),
+    };
+    let composed = compositor.compose(&appearance, 0, 4).unwrap();
+    //The 5x1 mount sits at the bottom, with the 3x3 rider raised a pixel and shifted right
+    assert_eq!(composed.frames[0].dimensions(), (5, 4));
+    assert_eq!((composed.canvas.anchor_x, composed.canvas.anchor_y), (2, 4));
+    assert_eq!(color(composed.frames[0].get_pixel(0, 3)), [0, 0, 255, 255]);
+    assert_eq!(color(composed.frames[0].get_pixel(2, 0)), [255, 0, 0, 255]);
+    assert_eq!(color(composed.frames[0].get_pixel(1, 0)), [0, 0, 0, 0]);
+
+    //Mirrored directions flip the whole composition
+    let mirrored = compositor.compose(&appearance, 0, 2).unwrap();
+    assert_eq!(color(mirrored.frames[0].get_pixel(2, 0)), [255, 0, 0, 255]);
+    assert_eq!(color(mirrored.frames[0].get_pixel(3, 0)), [0, 0, 0, 0]);
+    assert_eq!(mirrored.canvas.anchor_x, 3);
+}
@@ -0,0 +1,152 @@
+use super::test_anim_canvas::{example_frame, example_group};
+use crate::anim::AnimGroup;
+use crate::anim::canvas::AnimCanvas;
+use crate::anim::export::ExportOptions;
+use image::AnimationDecoder;
+use image::codecs::gif::GifDecoder;
+use image::codecs::png::PngDecoder;
+use std::io::Cursor;
+use std::time::Duration;
+
+fn two_frame_group() -> AnimGroup {
+    //A 4x2 frame anchored below its bottom middle, then a 2x5 frame anchored a pixel further
+    //below its bottom left
+    example_group(vec![example_frame(2, 0, 4, 2), example_frame(0, 1, 2, 5)])
+}
+
+#[test]
+fn test_canvas() {
+    let group = two_frame_group();
+    let frames = group.to_timed_frames(&ExportOptions::default());
+    assert_eq!(frames.len(), 2);
+    for frame in frames.iter() {
+        assert_eq!(frame.buffer().dimensions(), (4, 6));
+        assert_eq!(frame.delay().numer_denom_ms(), (100, 1));
+    }
+    //Each bottom row keeps its distance from the shared anchor
+    assert_eq!(frames[0].buffer().get_pixel(0, 5).0, [255, 255, 255, 255]);
+    assert_eq!(frames[0].buffer().get_pixel(0, 4).0, [0, 0, 0, 0]);
+    assert_eq!(frames[1].buffer().get_pixel(2, 4).0, [255, 255, 255, 255]);
+    assert_eq!(frames[1].buffer().get_pixel(2, 5).0, [0, 0, 0, 0]);
+    assert_eq!(frames[1].buffer().get_pixel(1, 4).0, [0, 0, 0, 0]);
+}
+
+#[test]
+fn test_gif() {
+    let options = ExportOptions {
+        frame_delays: vec![Duration::from_millis(250)],
+        ..ExportOptions::default()
+    };
+    let mut gif = vec![];
+    two_frame_group().write_gif(&mut gif, &options).unwrap();
+    let frames = GifDecoder::new(Cursor::new(gif))
+        .unwrap()
+        .into_frames()
+        .collect_frames()
+        .unwrap();
+    assert_eq!(frames.len(), 2);
+    assert_eq!(frames[0].delay().numer_denom_ms(), (250, 1));
+    assert_eq!(frames[1].delay().numer_denom_ms(), (100, 1));
+}
+
+fn find(data: &[u8], needle: &[u8]) -> Option<usize> {
+    data.windows(needle.len())
+        .position(|window| window == needle)
+}
+
+#[test]
+fn test_gif_looping() {
+    //Looping is stored in a NETSCAPE2.0 extension, which is left out to play once
+    let mut gif = vec![];
+    two_frame_group()
+        .write_gif(&mut gif, &ExportOptions::default())
+        .unwrap();
+    assert!(find(&gif, b"NETSCAPE2.0").is_some());
+
+    let options = ExportOptions {
+        looping: false,
+        ..ExportOptions::default()
+    };
+    let mut gif = vec![];
+    two_frame_group().write_gif(&mut gif, &options).unwrap();
+    assert!(find(&gif, b"NETSCAPE2.0").is_none());
+    let frames = GifDecoder::new(Cursor::new(gif))
+        .unwrap()
+        .into_frames()
+        .collect_frames()
+        .unwrap();
+    assert_eq!(frames.len(), 2);
+}
+
+#[test]
+fn test_apng() {
+    let mut apng = vec![];
+    two_frame_group()
+        .write_apng(&mut apng, &ExportOptions::default())
+        .unwrap();
+    let frames = PngDecoder::new(Cursor::new(apng))
+        .unwrap()
+        .apng()
+        .unwrap()
+        .into_frames()
+        .collect_frames()
+        .unwrap();
+    assert_eq!(frames.len(), 2);
+    assert_eq!(frames[1].buffer().dimensions(), (4, 6));
+    assert_eq!(frames[1].buffer().get_pixel(3, 4).0, [255, 255, 255, 255]);
+}
+
+#[test]
+fn test_apng_looping() {
+    //acTL holds the frame count then the number of plays, where 0 loops forever
+    let plays = |options: &ExportOptions| {
+        let mut apng = vec![];
+        two_frame_group().write_apng(&mut apng, options).unwrap();
+        let start = find(&apng, b"acTL").unwrap() + 4;
+        let frames = u32::from_be_bytes(apng[start..start + 4].try_into().unwrap());
+        assert_eq!(frames, 2);
+        u32::from_be_bytes(apng[start + 4..start + 8].try_into().unwrap())
+    };
+    assert_eq!(plays(&ExportOptions::default()), 0);
+    let options = ExportOptions {
+        looping: false,
+        ..ExportOptions::default()
+    };
+    assert_eq!(plays(&options), 1);
+}
+
+#[test]
+fn test_sprite_sheet() {
+    let sheet = two_frame_group()
+        .to_sprite_sheet(&ExportOptions::default())
+        .unwrap();
+    assert_eq!(sheet.image.dimensions(), (6, 5));
+    assert_eq!(
+        sheet.canvas,
+        AnimCanvas {
+            width: 4,
+            height: 6,
+            anchor_x: 2,
+            anchor_y: 6,
+        }
+    );
+    assert_eq!(sheet.frames[0].rect.width, 4);
+    assert_eq!((sheet.frames[0].offset_x, sheet.frames[0].offset_y), (0, 4));
+    assert_eq!(sheet.frames[1].rect.x, 4);
+    assert_eq!((sheet.frames[1].offset_x, sheet.frames[1].offset_y), (2, 0));
+}
+
+#[cfg(feature = "json")]
+#[test]
+fn test_sprite_sheet_json() {
+    let sheet = two_frame_group()
+        .to_sprite_sheet(&ExportOptions::default())
+        .unwrap();
+    let json: serde_json::Value = serde_json::from_str(&sheet.to_json("walk.png")).unwrap();
+    assert_eq!(json["meta"]["image"], "walk.png");
+    assert_eq!(json["meta"]["pivot"]["y"], 6);
+    assert_eq!(json["frames"]["1"]["frame"]["x"], 4);
+    assert_eq!(json["frames"]["1"]["spriteSourceSize"]["x"], 2);
+    assert_eq!(json["frames"]["1"]["sourceSize"]["h"], 6);
+    assert_eq!(json["frames"]["0"]["duration"], 100);
+}
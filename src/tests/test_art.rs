@@ -4,10 +4,18 @@ use std::io::{Cursor, Result};
 
 use byteorder::{LittleEndian, WriteBytesExt};
 
+use crate::art::{Art, ArtReader, ArtWriter, STATIC_OFFSET};
 #[cfg(feature = "image")]
-use crate::art::{Art, Tile};
-use crate::art::{ArtReader, STATIC_OFFSET};
+use crate::art::{RunPair, Static, Tile};
+use crate::bounds::Bounds;
+#[cfg(feature = "image")]
+use crate::color::NEAR_BLACK_16;
+#[cfg(feature = "image")]
+use crate::error::{FromImageError, MulWriterError};
+#[cfg(feature = "image")]
+use crate::map::radarcol::RadarColWriter;
 use crate::mul::tests::simple_from_vecs;
+use crate::mul::{MulReader, MulWriter};
 
 fn raw_tile_data() -> Result<Vec<u8>> {
     let mut data = Cursor::new(vec![]);
@@ -151,3 +159,336 @@ fn test_static_to_image() {
     assert_eq!(image.get_pixel(1, 2).channels(), white);
     assert_eq!(image.get_pixel(2, 2).channels(), transparent);
 }
+
+#[test]
+fn test_counts() {
+    let mut reader = example_art_mul(&raw_static().unwrap());
+    assert_eq!(reader.tile_count().unwrap(), STATIC_OFFSET);
+    assert_eq!(reader.static_count().unwrap(), 1);
+    assert!(reader.read_static(1).is_err());
+    assert!(reader.read_static(u32::MAX).is_err());
+
+    let mut reader = ArtReader::from_mul(simple_from_vecs(vec![(raw_tile_data().unwrap(), 0, 0)]));
+    assert_eq!(reader.tile_count().unwrap(), 1);
+    assert_eq!(reader.static_count().unwrap(), 0);
+    assert!(reader.read_tile(1).is_err());
+}
+
+#[test]
+fn test_read_extended_static() {
+    let mut padded = vec![];
+    for _i in 0..(STATIC_OFFSET + 0xC000) {
+        padded.push((vec![], 0, 0));
+    }
+    padded.push((raw_static().unwrap(), 0, 0));
+    let mut reader = ArtReader::from_mul(simple_from_vecs(padded));
+    assert_eq!(reader.static_count().unwrap(), 0xC001);
+    let stat = reader.read_static(0xC000).unwrap();
+    assert_eq!(stat.width, 3);
+}
+
+#[cfg(feature = "image")]
+#[test]
+fn test_tile_from_image() {
+    let tile = Tile {
+        header: 0,
+        // Only the first 1012 pixels make up the diamond
+        image_data: std::array::from_fn(|i| if i < 1012 { i as u16 } else { 0 }),
+    };
+    let mut image = tile.to_image();
+    let (converted, outside) = Tile::from_image(&image).unwrap();
+    assert_eq!(converted, tile);
+    assert!(outside.is_empty());
+
+    image.put_pixel(0, 0, image::Rgba([255, 0, 0, 255]));
+    image.put_pixel(43, 43, image::Rgba([255, 0, 0, 128]));
+    let (converted, outside) = Tile::from_image(&image).unwrap();
+    assert_eq!(converted, tile);
+    assert_eq!(outside, vec![(0, 0), (43, 43)]);
+
+    assert!(Tile::from_image(&image::RgbaImage::new(44, 45)).is_err());
+}
+
+#[cfg(feature = "image")]
+#[test]
+fn test_static_from_image() {
+    let white = 0x7FFF;
+    let stat = Static {
+        size: 0,
+        trigger: 0,
+        width: 3,
+        height: 3,
+        rows: vec![
+            vec![RunPair {
+                offset: 1,
+                run: vec![white],
+            }],
+            vec![RunPair {
+                offset: 0,
+                run: vec![white, white, white],
+            }],
+            vec![RunPair {
+                offset: 1,
+                run: vec![white],
+            }],
+        ],
+    };
+    let converted = Static::from_image(&stat.to_image()).unwrap();
+    assert_eq!(converted.rows, stat.rows);
+    assert_eq!(converted.width, 3);
+    assert_eq!(converted.height, 3);
+    let serialized = converted.serialize();
+    assert_eq!(converted.size as usize, serialized.len());
+    assert_eq!(&serialized[4..], &stat.serialize()[4..]);
+}
+
+#[cfg(feature = "image")]
+#[test]
+fn test_static_from_image_runs() {
+    let mut image = image::RgbaImage::new(6, 2);
+    let red = image::Rgba([255, 0, 0, 255]);
+    image.put_pixel(1, 0, red);
+    image.put_pixel(2, 0, image::Rgba([0, 0, 0, 255]));
+    image.put_pixel(4, 0, red);
+    image.put_pixel(5, 0, image::Rgba([0, 0, 255, 100]));
+    let stat = Static::from_image(&image).unwrap();
+    assert_eq!(
+        stat.rows[0],
+        vec![
+            RunPair {
+                offset: 1,
+                run: vec![0x7C00, NEAR_BLACK_16]
+            },
+            RunPair {
+                offset: 1,
+                run: vec![0x7C00]
+            }
+        ]
+    );
+    assert!(stat.rows[1].is_empty());
+
+    let mut reader = example_art_mul(&stat.serialize());
+    assert_eq!(reader.read_static(0).unwrap(), stat);
+
+    assert!(Static::from_image(&image::RgbaImage::new(1024, 1)).is_err());
+    //Too many opaque pixels for the row offsets and size to fit in a u16
+    let opaque = image::RgbaImage::from_pixel(300, 300, red);
+    assert!(Static::from_image(&opaque).is_err());
+}
+
+#[test]
+fn test_write_art() {
+    let mut idx = vec![];
+    let mut mul = vec![];
+    {
+        let mut writer = ArtWriter::from_mul(MulWriter::from_writables(
+            Cursor::new(&mut idx),
+            Cursor::new(&mut mul),
+        ));
+        let tile = ArtReader::from_mul(simple_from_vecs(vec![(raw_tile_data().unwrap(), 0, 0)]))
+            .read_tile(0)
+            .unwrap();
+        let stat = example_art_mul(&raw_static().unwrap())
+            .read_static(0)
+            .unwrap();
+        writer.write_tile(3, &tile).unwrap();
+        writer.write_static(1, &stat).unwrap();
+        writer.write_static(2, &stat).unwrap();
+        writer.delete_static(2).unwrap();
+        assert!(writer.write_tile(STATIC_OFFSET, &tile).is_err());
+        assert!(writer.write_static(u32::MAX, &stat).is_err());
+    }
+
+    let mut reader = ArtReader::from_mul(MulReader::from_readables(
+        Cursor::new(idx),
+        Cursor::new(mul),
+    ));
+    assert_eq!(reader.tile_count().unwrap(), STATIC_OFFSET);
+    assert_eq!(reader.static_count().unwrap(), 3);
+    assert_eq!(
+        reader.read_tile(3).unwrap().serialize(),
+        raw_tile_data().unwrap()
+    );
+    assert!(reader.read_tile(0).is_err());
+    assert_eq!(reader.read_static(1).unwrap().width, 3);
+    assert!(reader.read_static(2).is_err());
+}
+
+#[test]
+fn test_average_color() {
+    let tile = ArtReader::from_mul(simple_from_vecs(vec![(raw_tile_data().unwrap(), 0, 0)]))
+        .read_tile(0)
+        .unwrap();
+    //Alternating white and black averages to half-brightness in each channel
+    assert_eq!(tile.average_color(), (15 << 10) | (15 << 5) | 15);
+}
+
+/// Create an empty directory for importing from, unique to this test run
+#[cfg(feature = "image")]
+fn import_directory(name: &str) -> std::path::PathBuf {
+    let directory =
+        std::env::temp_dir().join(format!("uorustlibs_{}_{}", name, std::process::id()));
+    let _ = std::fs::remove_dir_all(&directory);
+    std::fs::create_dir_all(&directory).unwrap();
+    directory
+}
+
+#[cfg(feature = "image")]
+#[test]
+fn test_import_tiles() {
+    let directory = import_directory("test_art_import_tiles");
+    let red = image::RgbaImage::from_pixel(44, 44, image::Rgba([255, 0, 0, 255]));
+    let blue = image::RgbaImage::from_pixel(44, 44, image::Rgba([0, 0, 255, 255]));
+    red.save(directory.join("0x10.png")).unwrap();
+    blue.save(directory.join("2.png")).unwrap();
+    //Names which aren't hex ids are skipped
+    red.save(directory.join("grass.png")).unwrap();
+
+    let mut idx = vec![];
+    let mut mul = vec![];
+    let mut radarcol = vec![];
+    let ids = {
+        let mut writer = ArtWriter::from_mul(MulWriter::from_writables(
+            Cursor::new(&mut idx),
+            Cursor::new(&mut mul),
+        ));
+        let mut radarcol_writer = RadarColWriter::from_writable(Cursor::new(&mut radarcol));
+        writer
+            .import_tiles(&directory, Some(&mut radarcol_writer))
+            .unwrap()
+    };
+    assert_eq!(ids, vec![2, 0x10]);
+
+    let mut reader = ArtReader::from_mul(MulReader::from_readables(
+        Cursor::new(idx),
+        Cursor::new(mul),
+    ));
+    assert_eq!(reader.tile_count().unwrap(), 0x11);
+    let tile = reader.read_tile(0x10).unwrap();
+    assert_eq!(tile, Tile::from_image(&red).unwrap().0);
+    assert!(tile.image_data[..1012].iter().all(|&pixel| pixel == 0x7C00));
+    assert_eq!(reader.read_tile(2).unwrap().image_data[0], 0x001F);
+    assert_eq!(&radarcol[4..6], &[0x1F, 0x00]);
+
+    //Tiles have to be 44px square
+    image::RgbaImage::new(44, 45)
+        .save(directory.join("3.png"))
+        .unwrap();
+    let mut idx = vec![];
+    let mut mul = vec![];
+    let result = ArtWriter::from_mul(MulWriter::from_writables(
+        Cursor::new(&mut idx),
+        Cursor::new(&mut mul),
+    ))
+    .import_tiles::<Cursor<Vec<u8>>>(&directory, None);
+    std::fs::remove_dir_all(&directory).unwrap();
+    match result {
+        Err(MulWriterError::FromImage(FromImageError::InvalidImageSize { x, y })) => {
+            assert_eq!((x, y), (44, 45))
+        }
+        other => panic!("Unexpected result {:?}", other),
+    }
+}
+
+#[cfg(feature = "image")]
+#[test]
+fn test_import_statics() {
+    let directory = import_directory("test_art_import_statics");
+    let mut image = image::RgbaImage::new(2, 2);
+    image.put_pixel(0, 0, image::Rgba([255, 0, 0, 255]));
+    image.save(directory.join("0x0A.png")).unwrap();
+    image.save(directory.join("3.PNG")).unwrap();
+    image.save(directory.join("notes.png")).unwrap();
+    std::fs::write(directory.join("4.txt"), "not an image").unwrap();
+
+    let mut idx = vec![];
+    let mut mul = vec![];
+    let mut radarcol = vec![];
+    let ids = {
+        let mut writer = ArtWriter::from_mul(MulWriter::from_writables(
+            Cursor::new(&mut idx),
+            Cursor::new(&mut mul),
+        ));
+        let mut radarcol_writer = RadarColWriter::from_writable(Cursor::new(&mut radarcol));
+        writer
+            .import_statics(&directory, Some(&mut radarcol_writer))
+            .unwrap()
+    };
+    std::fs::remove_dir_all(&directory).unwrap();
+    assert_eq!(ids, vec![3, 0x0A]);
+
+    let mut reader = ArtReader::from_mul(MulReader::from_readables(
+        Cursor::new(idx),
+        Cursor::new(mul),
+    ));
+    assert_eq!(reader.static_count().unwrap(), 0x0B);
+    assert_eq!(
+        reader.read_static(0x0A).unwrap().rows[0][0].run,
+        vec![0x7C00]
+    );
+    let offset = (STATIC_OFFSET as usize + 3) * 2;
+    assert_eq!(&radarcol[offset..offset + 2], &[0x00, 0x7C]);
+}
+
+#[test]
+fn test_static_hit_test() {
+    let mut reader = example_art_mul(&raw_static().unwrap());
+    let mut stat = reader.read_static(0).unwrap();
+    assert!(!stat.hit_test(0, 0));
+    assert!(stat.hit_test(1, 0));
+    assert!(!stat.hit_test(2, 0));
+    assert!(stat.hit_test(0, 1));
+    assert!(stat.hit_test(2, 1));
+    assert!(!stat.hit_test(3, 1));
+    assert!(!stat.hit_test(1, 3));
+    assert_eq!(
+        stat.bounds(),
+        Some(Bounds {
+            x: 0,
+            y: 0,
+            width: 3,
+            height: 3
+        })
+    );
+
+    stat.rows[1].clear();
+    stat.rows[2].clear();
+    let bounds = stat.bounds().unwrap();
+    assert_eq!(
+        bounds,
+        Bounds {
+            x: 1,
+            y: 0,
+            width: 1,
+            height: 1
+        }
+    );
+    assert!(bounds.contains(1, 0));
+    assert!(!bounds.contains(1, 1));
+
+    stat.rows[0].clear();
+    assert_eq!(stat.bounds(), None);
+}
+
+#[test]
+fn test_tile_hit_test() {
+    let tile = ArtReader::from_mul(simple_from_vecs(vec![(raw_tile_data().unwrap(), 0, 0)]))
+        .read_tile(0)
+        .unwrap();
+    assert!(!tile.hit_test(0, 0));
+    assert!(tile.hit_test(21, 0));
+    assert!(tile.hit_test(22, 0));
+    assert!(!tile.hit_test(23, 0));
+    assert!(tile.hit_test(0, 21));
+    assert!(tile.hit_test(43, 22));
+    assert!(!tile.hit_test(22, 44));
+    assert_eq!(
+        tile.bounds(),
+        Some(Bounds {
+            x: 0,
+            y: 0,
+            width: 44,
+            height: 44
+        })
+    );
+}
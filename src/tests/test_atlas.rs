@@ -0,0 +1,154 @@
+use crate::art::{RunPair, Static, Tile};
+use crate::atlas::{AssetKind, AtlasBuilder, AtlasOptions};
+use crate::bounds::Bounds;
+use crate::gump::{Gump, GumpPair};
+use crate::texmap::TexMap;
+use image::Pixel;
+
+fn example_static(width: u16, height: u16) -> Static {
+    //A single red pixel in the middle of the bottom row
+    let mut rows = vec![vec![]; height as usize];
+    rows[height as usize - 1] = vec![RunPair {
+        offset: width / 2,
+        run: vec![0x7C00],
+    }];
+    Static {
+        size: 0,
+        trigger: 0,
+        width,
+        height,
+        rows,
+    }
+}
+
+fn example_gump(width: u16, height: u16) -> Gump {
+    Gump {
+        width,
+        height,
+        data: vec![
+            vec![GumpPair {
+                color: 0x001F,
+                count: width
+            }];
+            height as usize
+        ],
+    }
+}
+
+#[test]
+fn test_pack() {
+    let mut builder = AtlasBuilder::new(AtlasOptions {
+        max_width: 80,
+        max_height: 80,
+        padding: 1,
+        trim: true,
+    });
+    builder.add_tile(
+        2,
+        &Tile {
+            header: 0,
+            image_data: [0x03E0; 1022],
+        },
+    );
+    builder.add_static(5, &example_static(9, 20));
+    builder.add_gump(1, &example_gump(10, 4)).unwrap();
+    builder.add_texmap(
+        3,
+        &TexMap {
+            data: vec![0x7FFF; 64 * 64],
+        },
+    );
+    let atlas = builder.build().unwrap();
+    assert_eq!(atlas.entries.len(), 4);
+
+    //The texmap and tile can't share an 80px page
+    assert_eq!(atlas.pages.len(), 2);
+    let texmap = atlas.entry(AssetKind::TexMap, 3).unwrap();
+    assert_eq!(texmap.rect.width, 64);
+    assert_ne!(texmap.page, atlas.entry(AssetKind::Tile, 2).unwrap().page);
+    //The smaller assets fill the space beside the texmap
+    assert_eq!(atlas.pages[texmap.page].dimensions(), (79, 66));
+
+    let stat = atlas.entry(AssetKind::Static, 5).unwrap();
+    assert_eq!(stat.source_width, 9);
+    assert_eq!(stat.source_height, 20);
+    assert_eq!(
+        stat.source,
+        Bounds {
+            x: 4,
+            y: 19,
+            width: 1,
+            height: 1
+        }
+    );
+    assert_eq!(stat.rect.width, 1);
+    assert_eq!(stat.anchor_x, 0);
+    assert_eq!(stat.anchor_y, 1);
+    let page = &atlas.pages[stat.page];
+    assert_eq!(
+        page.get_pixel(stat.rect.x, stat.rect.y).channels(),
+        [255, 0, 0, 255]
+    );
+
+    let tile = atlas.entry(AssetKind::Tile, 2).unwrap();
+    assert_eq!(tile.rect.width, 44);
+    assert_eq!((tile.anchor_x, tile.anchor_y), (22, 22));
+
+    //Nothing overlaps on a page, including padding
+    for a in atlas.entries.iter() {
+        assert!(a.rect.x >= 1 && a.rect.y >= 1);
+        assert!(a.rect.x + a.rect.width < atlas.pages[a.page].width() + 1);
+        for b in atlas.entries.iter() {
+            if a == b || a.page != b.page {
+                continue;
+            }
+            let apart = a.rect.x + a.rect.width < b.rect.x + 1
+                || b.rect.x + b.rect.width < a.rect.x + 1
+                || a.rect.y + a.rect.height < b.rect.y + 1
+                || b.rect.y + b.rect.height < a.rect.y + 1;
+            assert!(apart, "{:?} overlaps {:?}", a, b);
+        }
+    }
+}
+
+#[test]
+fn test_too_large() {
+    let mut builder = AtlasBuilder::new(AtlasOptions {
+        max_width: 32,
+        max_height: 32,
+        ..AtlasOptions::default()
+    });
+    builder.add_gump(0, &example_gump(40, 2)).unwrap();
+    assert!(builder.build().is_err());
+}
+
+#[test]
+fn test_untrimmed() {
+    let mut builder = AtlasBuilder::new(AtlasOptions {
+        trim: false,
+        padding: 0,
+        ..AtlasOptions::default()
+    });
+    builder.add_static(0, &example_static(9, 20));
+    let atlas = builder.build().unwrap();
+    let entry = atlas.entry(AssetKind::Static, 0).unwrap();
+    assert_eq!(entry.rect.width, 9);
+    assert_eq!(entry.rect.height, 20);
+    assert_eq!((entry.anchor_x, entry.anchor_y), (4, 20));
+    assert_eq!(atlas.pages[0].dimensions(), (9, 20));
+}
+
+#[cfg(feature = "json")]
+#[test]
+fn test_to_json() {
+    let mut builder = AtlasBuilder::new(AtlasOptions::default());
+    builder.add_static(7, &example_static(3, 3));
+    let manifest: serde_json::Value = serde_json::from_str(&builder.build().unwrap().to_json())
+        .expect("Manifest should be valid JSON");
+    assert_eq!(manifest["pages"][0]["width"], 3);
+    assert_eq!(manifest["entries"][0]["kind"], "static");
+    assert_eq!(manifest["entries"][0]["id"], 7);
+    assert_eq!(manifest["entries"][0]["x"], 1);
+    assert_eq!(manifest["entries"][0]["anchor_x"], 0);
+    assert_eq!(manifest["entries"][0]["source_x"], 1);
+}
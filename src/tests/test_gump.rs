@@ -1,5 +1,11 @@
-use crate::gump::GumpReader;
+use crate::bounds::Bounds;
+#[cfg(feature = "image")]
+use crate::color::{Color, NEAR_BLACK_16};
+#[cfg(feature = "image")]
+use crate::gump::Gump;
+use crate::gump::{GumpPair, GumpReader, GumpWriter};
 use crate::mul::tests::simple_from_vecs;
+use crate::mul::{MulReader, MulWriter};
 use byteorder::{LittleEndian, WriteBytesExt};
 use std::io::{Cursor, Result};
 
@@ -72,3 +78,173 @@ fn test_to_image() {
     assert_eq!(image.get_pixel(1, 2).channels(), white);
     assert_eq!(image.get_pixel(2, 2).channels(), transparent);
 }
+
+#[test]
+fn test_hit_test() {
+    let mul_reader = simple_from_vecs(vec![example_gump_mul().unwrap()]);
+    let mut reader = GumpReader::from_mul(mul_reader);
+    let mut gump = reader.read(0).unwrap();
+    assert!(!gump.hit_test(0, 0));
+    assert!(gump.hit_test(1, 0));
+    assert!(gump.hit_test(2, 1));
+    assert!(!gump.hit_test(2, 2));
+    assert!(!gump.hit_test(3, 1));
+    assert!(!gump.hit_test(0, 3));
+    assert_eq!(
+        gump.bounds(),
+        Some(Bounds {
+            x: 0,
+            y: 0,
+            width: 3,
+            height: 3
+        })
+    );
+
+    gump.data[1] = vec![GumpPair { color: 0, count: 3 }];
+    assert_eq!(
+        gump.bounds(),
+        Some(Bounds {
+            x: 1,
+            y: 0,
+            width: 1,
+            height: 3
+        })
+    );
+}
+
+#[test]
+fn test_serialize() {
+    let (raw, _, _) = example_gump_mul().unwrap();
+    let mul_reader = simple_from_vecs(vec![example_gump_mul().unwrap()]);
+    let gump = GumpReader::from_mul(mul_reader).read(0).unwrap();
+    assert_eq!(gump.serialize(), raw);
+}
+
+#[test]
+fn test_write() {
+    let mul_reader = simple_from_vecs(vec![example_gump_mul().unwrap()]);
+    let gump = GumpReader::from_mul(mul_reader).read(0).unwrap();
+    let mut wide = gump.clone();
+    wide.width = 4;
+    for row in wide.data.iter_mut() {
+        row.push(GumpPair {
+            color: 0x001F,
+            count: 1,
+        });
+    }
+
+    let mut idx = vec![];
+    let mut mul = vec![];
+    {
+        let mut writer = GumpWriter::from_mul(MulWriter::from_writables(
+            Cursor::new(&mut idx),
+            Cursor::new(&mut mul),
+        ));
+        writer.write(0, &gump).unwrap();
+        writer.write(2, &gump).unwrap();
+        writer.write(2, &wide).unwrap();
+        writer.delete(0).unwrap();
+    }
+
+    let mut reader = GumpReader::from_mul(MulReader::from_readables(
+        Cursor::new(idx),
+        Cursor::new(mul),
+    ));
+    assert!(reader.read(0).is_err());
+    assert!(reader.read(1).is_err());
+    assert_eq!(reader.read(2).unwrap(), wide);
+}
+
+#[test]
+#[cfg(feature = "image")]
+fn test_from_image() {
+    let mul_reader = simple_from_vecs(vec![example_gump_mul().unwrap()]);
+    let gump = GumpReader::from_mul(mul_reader).read(0).unwrap();
+    let image = gump.to_image().unwrap();
+    let imported = Gump::from_image(&image).unwrap();
+    assert_eq!((imported.width, imported.height), (3, 3));
+    assert_eq!(imported.data.len(), gump.data.len());
+    assert_eq!(imported.data[0].len(), 3);
+    assert_eq!(
+        imported.data[1],
+        vec![GumpPair {
+            color: 0x7FFF,
+            count: 3
+        }]
+    );
+    assert_eq!(imported.to_image().unwrap(), image);
+
+    //Opaque black can't be stored as black, or it would become transparent
+    let mut black = image::RgbaImage::new(2, 1);
+    black.put_pixel(1, 0, image::Rgba([0, 0, 0, 255]));
+    let gump = Gump::from_image(&black).unwrap();
+    assert_eq!(
+        gump.data,
+        vec![vec![
+            GumpPair { color: 0, count: 1 },
+            GumpPair {
+                color: NEAR_BLACK_16,
+                count: 1
+            }
+        ]]
+    );
+    assert!(gump.hit_test(1, 0));
+    assert!(Gump::from_image(&image::RgbaImage::new(0, 4)).is_err());
+}
+
+#[test]
+#[cfg(feature = "image")]
+fn test_render_resizepic() {
+    //Each piece is a solid block with its own color
+    let sizes = [
+        (2, 2),
+        (3, 2),
+        (2, 2),
+        (2, 3),
+        (3, 3),
+        (2, 3),
+        (2, 2),
+        (3, 2),
+        (2, 2),
+    ];
+    let mut records = vec![(vec![], 0, 0)];
+    for (idx, (width, height)) in sizes.into_iter().enumerate() {
+        let gump = Gump {
+            width,
+            height,
+            data: vec![
+                vec![GumpPair {
+                    color: idx as u16 + 1,
+                    count: width
+                }];
+                height as usize
+            ],
+        };
+        records.push((gump.serialize(), height, width));
+    }
+    let mut reader = GumpReader::from_mul(simple_from_vecs(records));
+    assert!(reader.read_resizepic(2).is_err());
+    let resizepic = reader.read_resizepic(1).unwrap();
+    let image = resizepic.render(9, 8).unwrap();
+    assert_eq!(image.dimensions(), (9, 8));
+
+    let piece_at = |x, y| {
+        let pixel = image.get_pixel(x, y);
+        (0..9u16)
+            .find(|idx| pixel == &image::Rgba((idx + 1).to_rgba().into()))
+            .unwrap()
+    };
+    assert_eq!(piece_at(0, 0), 0);
+    assert_eq!(piece_at(2, 0), 1);
+    //The top edge repeats, and is clipped before the corner
+    assert_eq!(piece_at(5, 1), 1);
+    assert_eq!(piece_at(6, 1), 1);
+    assert_eq!(piece_at(7, 0), 2);
+    assert_eq!(piece_at(1, 5), 3);
+    assert_eq!(piece_at(4, 4), 4);
+    assert_eq!(piece_at(6, 5), 4);
+    assert_eq!(piece_at(8, 3), 5);
+    assert_eq!(piece_at(0, 7), 6);
+    assert_eq!(piece_at(6, 6), 7);
+    assert_eq!(piece_at(8, 7), 8);
+}
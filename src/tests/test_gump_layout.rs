@@ -0,0 +1,126 @@
+use crate::gump::layout::{ButtonAction, GumpCommand, GumpLayout};
+
+const LAYOUT: &str = "{ nomove }{ page 0 }{ resizepic 0 0 9200 300 200 }\
+    { gumppic 10 -5 0x1F4 hue=33 }{ button 20 30 4005 4007 1 0 12 }\
+    { page 1 }{ text 40 50 0 1 }{ checkbox 5 5 210 211 1 3 }\
+    { page 2 }{ button 20 60 4005 4007 0 1 0 }{ textentrylimited 1 2 100 20 0 4 0 12 }\
+    { xmfhtmlgumpcolor 0 0 50 50 1011036 1 0 32767 }{ mastergump 5 }";
+
+#[test]
+fn test_parse() {
+    let layout = GumpLayout::parse(LAYOUT, vec!["Hello".to_string(), "World".to_string()]).unwrap();
+    assert_eq!(layout.commands.len(), 13);
+    assert_eq!(layout.commands[0], GumpCommand::NoMove);
+    assert_eq!(
+        layout.commands[2],
+        GumpCommand::ResizePic {
+            x: 0,
+            y: 0,
+            gump_id: 9200,
+            width: 300,
+            height: 200
+        }
+    );
+    assert_eq!(
+        layout.commands[3],
+        GumpCommand::GumpPic {
+            x: 10,
+            y: -5,
+            gump_id: 0x1F4,
+            hue: 33
+        }
+    );
+    assert_eq!(
+        layout.commands[4],
+        GumpCommand::Button {
+            x: 20,
+            y: 30,
+            up_id: 4005,
+            down_id: 4007,
+            action: ButtonAction::Reply(12)
+        }
+    );
+    assert_eq!(
+        layout.commands[7],
+        GumpCommand::CheckBox {
+            x: 5,
+            y: 5,
+            off_id: 210,
+            on_id: 211,
+            checked: true,
+            switch_id: 3
+        }
+    );
+    assert!(matches!(
+        layout.commands[9],
+        GumpCommand::Button {
+            action: ButtonAction::Page(1),
+            ..
+        }
+    ));
+    assert!(matches!(
+        layout.commands[10],
+        GumpCommand::TextEntry {
+            entry_id: 4,
+            max_length: Some(12),
+            ..
+        }
+    ));
+    assert!(matches!(
+        layout.commands[11],
+        GumpCommand::XmfHtmlGump {
+            cliloc: 1011036,
+            background: true,
+            scrollbar: false,
+            color: Some(32767),
+            ..
+        }
+    ));
+    assert_eq!(
+        layout.commands[12],
+        GumpCommand::Unknown {
+            name: "mastergump".to_string(),
+            args: vec!["5".to_string()]
+        }
+    );
+    assert_eq!(layout.text_line(1), "World");
+    assert_eq!(layout.text_line(2), "");
+}
+
+#[test]
+fn test_pages() {
+    let layout = GumpLayout::parse(LAYOUT, vec![]).unwrap();
+    assert_eq!(layout.pages(), vec![0, 1, 2]);
+    //Page 0 is drawn beneath every page
+    let page = layout.page_commands(1);
+    assert_eq!(page.len(), 6);
+    assert_eq!(page[0], &GumpCommand::NoMove);
+    assert!(matches!(page[4], GumpCommand::Text { text_id: 1, .. }));
+    assert_eq!(layout.page_commands(0).len(), 4);
+    assert_eq!(layout.page_commands(3).len(), 4);
+}
+
+#[test]
+fn test_parse_errors() {
+    assert!(GumpLayout::parse("{ page 0 }{ resizepic 0 0 9200 300 }", vec![]).is_err());
+    assert!(GumpLayout::parse("{ page zero }", vec![]).is_err());
+    assert!(GumpLayout::parse("{ text 0 0 0 -1 }", vec![]).is_err());
+    assert!(GumpLayout::parse("{ page 0 ", vec![]).is_err());
+    assert!(GumpLayout::parse("{ page 0 }}", vec![]).is_err());
+    assert_eq!(
+        GumpLayout::parse("  {}{ NoClose } ", vec![])
+            .unwrap()
+            .commands,
+        vec![GumpCommand::NoClose]
+    );
+}
+
+#[test]
+fn test_serialize() {
+    let layout = GumpLayout::parse(LAYOUT, vec![]).unwrap();
+    let serialized = layout.serialize();
+    assert!(serialized.starts_with("{ nomove }{ page 0 }{ resizepic 0 0 9200 300 200 }"));
+    assert!(serialized.contains("{ gumppic 10 -5 500 hue=33 }"));
+    assert!(serialized.contains("{ button 20 60 4005 4007 0 1 0 }"));
+    assert_eq!(GumpLayout::parse(&serialized, vec![]).unwrap(), layout);
+}
@@ -0,0 +1,101 @@
+use crate::gump::layout::{GumpCommand, GumpLayout};
+use crate::gump::packet::GumpPacket;
+use byteorder::{BigEndian, WriteBytesExt};
+use std::io::Write;
+
+fn example_packet() -> GumpPacket {
+    GumpPacket {
+        serial: 0x00001234,
+        gump_id: 0xABCD,
+        x: 50,
+        y: -10,
+        layout: GumpLayout::parse(
+            "{ page 0 }{ resizepic 0 0 9200 300 200 }{ text 20 20 0 0 }{ htmlgump 5 5 90 40 1 1 0 }",
+            vec!["Hello".to_string(), "Ünïcödé ✓".to_string()],
+        )
+        .unwrap(),
+    }
+}
+
+#[test]
+fn test_decode_classic() {
+    let layout = b"{ page 0 }{ text 1 2 0 0 }\0";
+    let mut data = vec![0xB0, 0, 0];
+    data.write_u32::<BigEndian>(1).unwrap(); //Serial
+    data.write_u32::<BigEndian>(2).unwrap(); //Gump id
+    data.write_i32::<BigEndian>(3).unwrap(); //X
+    data.write_i32::<BigEndian>(4).unwrap(); //Y
+    data.write_u16::<BigEndian>(layout.len() as u16).unwrap();
+    data.write_all(layout).unwrap();
+    data.write_u16::<BigEndian>(1).unwrap(); //Line count
+    data.write_u16::<BigEndian>(2).unwrap(); //Line length
+    data.write_all(&[0x00, 0x48, 0x00, 0x69]).unwrap(); //"Hi"
+    let length = data.len() as u16;
+    data[1..3].copy_from_slice(&length.to_be_bytes());
+    //Anything past the packet's length is ignored
+    data.push(0xFF);
+
+    let packet = GumpPacket::decode(&data).unwrap();
+    assert_eq!(
+        (packet.serial, packet.gump_id, packet.x, packet.y),
+        (1, 2, 3, 4)
+    );
+    assert_eq!(packet.layout.commands.len(), 2);
+    assert_eq!(
+        packet.layout.commands[1],
+        GumpCommand::Text {
+            x: 1,
+            y: 2,
+            hue: 0,
+            text_id: 0
+        }
+    );
+    assert_eq!(packet.layout.text, vec!["Hi".to_string()]);
+
+    assert!(GumpPacket::decode(&data[..length as usize - 1]).is_err());
+    data[0] = 0xB1;
+    assert!(GumpPacket::decode(&data).is_err());
+}
+
+#[test]
+fn test_encode_classic() {
+    let packet = example_packet();
+    let data = packet.encode_classic().unwrap();
+    assert_eq!(data[0], 0xB0);
+    assert_eq!(u16::from_be_bytes([data[1], data[2]]) as usize, data.len());
+    assert_eq!(&data[3..7], &[0x00, 0x00, 0x12, 0x34]);
+    assert_eq!(&data[15..19], &[0xFF, 0xFF, 0xFF, 0xF6]);
+    assert_eq!(GumpPacket::decode(&data).unwrap(), packet);
+}
+
+#[cfg(feature = "zlib")]
+#[test]
+fn test_compressed() {
+    let packet = example_packet();
+    let data = packet.encode_compressed().unwrap();
+    assert_eq!(data[0], 0xDD);
+    assert_eq!(u16::from_be_bytes([data[1], data[2]]) as usize, data.len());
+    //The uncompressed layout length includes its null terminator
+    let layout = packet.layout.serialize();
+    assert_eq!(
+        u32::from_be_bytes(data[23..27].try_into().unwrap()) as usize,
+        layout.len() + 1
+    );
+    assert_eq!(GumpPacket::decode(&data).unwrap(), packet);
+
+    //Gumps without text send an empty block
+    let mut silent = packet.clone();
+    silent.layout.text.clear();
+    let data = silent.encode_compressed().unwrap();
+    assert_eq!(&data[data.len() - 8..], &[0, 0, 0, 0, 0, 0, 0, 0]);
+    assert_eq!(GumpPacket::decode(&data).unwrap(), silent);
+
+    //Blocks have to fit within the packet, and inflate to exactly their stated length
+    let data = packet.encode_compressed().unwrap();
+    let mut oversized = data.clone();
+    oversized[19..23].copy_from_slice(&u32::MAX.to_be_bytes());
+    assert!(GumpPacket::decode(&oversized).is_err());
+    let mut understated = data.clone();
+    understated[23..27].copy_from_slice(&(layout.len() as u32).to_be_bytes());
+    assert!(GumpPacket::decode(&understated).is_err());
+}
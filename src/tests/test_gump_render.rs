@@ -0,0 +1,184 @@
+use crate::art::{Art, ArtReader, STATIC_OFFSET, Static};
+use crate::bounds::Bounds;
+use crate::font::{Character, Font};
+use crate::gump::layout::{ButtonAction, GumpLayout};
+use crate::gump::render::{ElementKind, GumpElement, GumpRenderer};
+use crate::gump::{Gump, GumpPair, GumpReader};
+use crate::hue::{Hue, HueGroup, HueReader};
+use crate::mul::tests::simple_from_vecs;
+use std::io::Cursor;
+
+const RED: [u8; 4] = [255, 0, 0, 255];
+const GREEN: [u8; 4] = [0, 255, 0, 255];
+const BLUE: [u8; 4] = [0, 0, 255, 255];
+const WHITE: [u8; 4] = [255, 255, 255, 255];
+
+fn solid_gump(width: u16, height: u16, color: u16) -> (Vec<u8>, u16, u16) {
+    let gump = Gump {
+        width,
+        height,
+        data: vec![
+            vec![GumpPair {
+                color,
+                count: width
+            }];
+            height as usize
+        ],
+    };
+    (gump.serialize(), height, width)
+}
+
+fn example_gumps() -> GumpReader<Cursor<Vec<u8>>> {
+    //0 to 8 are a red resizepic, 10 a white button, and 11 and 12 checkbox states
+    let mut records = vec![solid_gump(2, 2, 0x7C00); 9];
+    records.push((vec![], 0, 0));
+    records.push(solid_gump(4, 3, 0x7FFF));
+    records.push(solid_gump(2, 2, 0x7FFF));
+    records.push(solid_gump(2, 2, 0x03E0));
+    GumpReader::from_mul(simple_from_vecs(records))
+}
+
+fn example_art() -> ArtReader<Cursor<Vec<u8>>> {
+    let mut image = image::RgbaImage::new(2, 2);
+    for pixel in image.pixels_mut() {
+        *pixel = image::Rgba(GREEN);
+    }
+    let art = Static::from_image(&image).unwrap();
+    let mut records = vec![(vec![], 0, 0); STATIC_OFFSET as usize];
+    records.push((art.serialize(), 0, 0));
+    ArtReader::from_mul(simple_from_vecs(records))
+}
+
+fn example_fonts() -> Vec<Font> {
+    //Every character is a single white column, two pixels high
+    let font = Font {
+        header: 0,
+        characters: vec![
+            Character {
+                width: 1,
+                height: 2,
+                unknown: 0,
+                data: vec![0x7FFF; 2],
+            };
+            224
+        ],
+    };
+    vec![font; 10]
+}
+
+fn example_hues() -> HueReader<Cursor<Vec<u8>>> {
+    let hue = Hue::new([0x001F; 32], 0, 0, "Blue".to_string());
+    let group = HueGroup::new(0, std::array::from_fn(|_| hue.clone()));
+    HueReader::from_readable(Cursor::new(group.serialize()))
+}
+
+const LAYOUT: &str = "{ nomove }{ page 0 }{ resizepic 0 0 0 10 8 }{ button 6 5 10 10 1 0 7 }\
+    { page 1 }{ gumppic 2 2 11 hue=1 }{ checkbox 4 2 11 12 1 3 }{ text 0 0 1 0 }\
+    { page 2 }{ tilepic 12 0 0 }{ textentry 1 1 3 2 0 5 1 }{ checkertrans 0 0 2 2 }\
+    { htmlgump 0 8 2 4 2 0 0 }";
+
+#[test]
+fn test_render_page() {
+    let layout = GumpLayout::parse(LAYOUT, vec!["ab".to_string(), "c".to_string()]).unwrap();
+    let (mut gumps, mut art, mut hues) = (example_gumps(), example_art(), example_hues());
+    let fonts = example_fonts();
+    let mut renderer = GumpRenderer::new(&mut gumps, &mut art, &fonts, Some(&mut hues));
+
+    let page = renderer.render(&layout, 1).unwrap();
+    assert_eq!(page.image.dimensions(), (10, 8));
+    assert_eq!(page.image.get_pixel(9, 0).0, RED);
+    //The hued gump, the checked checkbox and the hued text
+    assert_eq!(page.image.get_pixel(3, 3).0, BLUE);
+    assert_eq!(page.image.get_pixel(4, 2).0, GREEN);
+    assert_eq!(page.image.get_pixel(1, 1).0, BLUE);
+    assert_eq!(page.image.get_pixel(2, 1).0, RED);
+    assert_eq!(page.image.get_pixel(9, 7).0, WHITE);
+    assert_eq!(
+        page.elements,
+        vec![
+            GumpElement {
+                kind: ElementKind::Button(ButtonAction::Reply(7)),
+                bounds: Bounds {
+                    x: 6,
+                    y: 5,
+                    width: 4,
+                    height: 3
+                }
+            },
+            GumpElement {
+                kind: ElementKind::CheckBox {
+                    switch_id: 3,
+                    checked: true
+                },
+                bounds: Bounds {
+                    x: 4,
+                    y: 2,
+                    width: 2,
+                    height: 2
+                }
+            },
+        ]
+    );
+}
+
+#[test]
+fn test_render_second_page() {
+    let text = vec!["ab".to_string(), "c".to_string(), "ab <b>c</b>".to_string()];
+    let layout = GumpLayout::parse(LAYOUT, text).unwrap();
+    let (mut gumps, mut art) = (example_gumps(), example_art());
+    let fonts = example_fonts();
+    let mut renderer =
+        GumpRenderer::<_, _, Cursor<Vec<u8>>>::new(&mut gumps, &mut art, &fonts, None);
+
+    let page = renderer.render(&layout, 2).unwrap();
+    //The static and the HTML box stretch the image
+    assert_eq!(page.image.dimensions(), (14, 12));
+    assert_eq!(page.image.get_pixel(13, 1).0, GREEN);
+    //Text entries draw their starting text, unhued without a HueReader
+    assert_eq!(page.image.get_pixel(1, 1).0[..3], WHITE[..3]);
+    assert_eq!(page.image.get_pixel(2, 2).0, RED);
+    //Everything beneath the checkertrans is translucent
+    assert_eq!(page.image.get_pixel(0, 0).0, [255, 0, 0, 127]);
+    //HTML text is wrapped to its box
+    assert_eq!(page.image.get_pixel(1, 8).0, WHITE);
+    assert_eq!(page.image.get_pixel(0, 10).0, WHITE);
+    assert_eq!(page.image.get_pixel(1, 10).0, [0, 0, 0, 0]);
+    assert_eq!(
+        page.elements[1],
+        GumpElement {
+            kind: ElementKind::TextEntry { entry_id: 5 },
+            bounds: Bounds {
+                x: 1,
+                y: 1,
+                width: 3,
+                height: 2
+            }
+        }
+    );
+}
+
+#[test]
+fn test_render_too_large() {
+    let (mut gumps, mut art) = (example_gumps(), example_art());
+    let fonts = example_fonts();
+    let mut renderer =
+        GumpRenderer::<_, _, Cursor<Vec<u8>>>::new(&mut gumps, &mut art, &fonts, None);
+
+    for layout in [
+        "{ resizepic 0 0 0 100000 8 }",
+        "{ gumppictiled 0 0 8 100000 11 }",
+        "{ croppedtext 0 0 100000 100000 0 0 }",
+        "{ textentry 0 0 100000 2 0 5 0 }",
+        "{ htmlgump 0 0 100000 4 0 0 0 }",
+        "{ xmfhtmlgump 0 0 2 100000 1000 1 0 }",
+        //Small elements can't be placed far enough away to make the page too large either
+        "{ gumppic 100000 0 11 }",
+    ] {
+        let layout = GumpLayout::parse(layout, vec!["ab".to_string()]).unwrap();
+        assert!(
+            renderer.render(&layout, 0).is_err(),
+            "{}",
+            layout.serialize()
+        );
+    }
+}
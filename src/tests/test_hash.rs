@@ -0,0 +1,102 @@
+use crate::art::{RunPair, Static, Tile};
+use crate::gump::{Gump, GumpPair};
+use crate::hash::{ContentHash, DuplicateFinder};
+
+fn gradient_static(shift: u16) -> Static {
+    //A left-to-right blue gradient, with an optional brighter stripe
+    let rows = (0..16)
+        .map(|y| {
+            let run = (0..16)
+                .map(|x| {
+                    if shift > 0 && y == 3 && x == 3 {
+                        0x7FFF
+                    } else {
+                        x * 2
+                    }
+                })
+                .collect();
+            vec![RunPair { offset: 0, run }]
+        })
+        .collect();
+    Static {
+        size: 0,
+        trigger: 0,
+        width: 16,
+        height: 16,
+        rows,
+    }
+}
+
+#[test]
+fn test_exact_hash() {
+    let a = gradient_static(0);
+    let b = gradient_static(0);
+    let c = gradient_static(1);
+    assert_eq!(a.content_hash(), b.content_hash());
+    assert_ne!(a.content_hash().exact, c.content_hash().exact);
+
+    //A transparent gap is not the same as a black pixel
+    let mut gap = a.clone();
+    gap.rows[0][0].offset = 1;
+    gap.rows[0][0].run.remove(0);
+    assert_ne!(a.content_hash().exact, gap.content_hash().exact);
+}
+
+#[test]
+fn test_perceptual_hash() {
+    let a = gradient_static(0).content_hash();
+    let c = gradient_static(1).content_hash();
+    assert!(a.distance(&c) <= 4);
+    assert_ne!(a.perceptual, 0);
+
+    //A flipped gradient is nothing alike
+    let mut flipped = gradient_static(0);
+    for row in flipped.rows.iter_mut() {
+        row[0].run.reverse();
+    }
+    assert!(a.distance(&flipped.content_hash()) > 32);
+
+    let tiny = Gump {
+        width: 1,
+        height: 1,
+        data: vec![vec![GumpPair {
+            color: 0x7FFF,
+            count: 1,
+        }]],
+    };
+    assert_eq!(tiny.content_hash().perceptual, 0);
+}
+
+#[test]
+fn test_tile_grid() {
+    let tile = Tile {
+        header: 0,
+        image_data: [0x7FFF; 1022],
+    };
+    let grid = tile.pixel_grid();
+    assert_eq!(
+        grid.pixels.iter().filter(|pixel| pixel.is_some()).count(),
+        1012
+    );
+    assert_eq!(grid.pixels[0], None);
+    assert_eq!(grid.pixels[21], Some(0x7FFF));
+}
+
+#[test]
+fn test_groups() {
+    let mut finder = DuplicateFinder::new();
+    finder.add(10, &gradient_static(0));
+    finder.add(3, &gradient_static(0));
+    finder.add(7, &gradient_static(1));
+    let mut flipped = gradient_static(0);
+    for row in flipped.rows.iter_mut() {
+        row[0].run.reverse();
+    }
+    finder.add(1, &flipped);
+
+    assert_eq!(finder.exact_groups(), vec![vec![3, 10]]);
+    assert_eq!(finder.similar_groups(0), vec![vec![3, 10]]);
+    assert_eq!(finder.similar_groups(4), vec![vec![3, 7, 10]]);
+    assert_eq!(finder.hash(7), Some(gradient_static(1).content_hash()));
+    assert_eq!(finder.hash(2), None);
+}
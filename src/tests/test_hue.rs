@@ -1,6 +1,7 @@
 use std::io::{Cursor, Write};
 
-use crate::hue::{Hue, HueGroup, HueReader};
+use crate::error::MulWriterError;
+use crate::hue::{Hue, HueGroup, HueReader, HueWriter};
 
 fn make_color_table(color: u16) -> [u16; 32] {
     [color; 32]
@@ -60,6 +61,10 @@ fn test_load_hues() {
     assert_eq!(second.entries[0].table_end, 4);
     assert_eq!(second.entries[0].color_table[0], 1);
     assert_eq!(second.entries[0].name, "Llama".to_string());
+
+    let single = reader.read_hue(9).expect("Couldn't read hue 9");
+    assert_eq!(single.table_start, 3);
+    assert_eq!(single.name, "Llama".to_string());
 }
 
 #[test]
@@ -108,3 +113,69 @@ fn test_serialize_hue_group() {
     assert_eq!(serialized[0], 5);
     assert_eq!(serialized[4 + 64], 1);
 }
+
+fn make_group(header: u32, color: u16, name: &str) -> HueGroup {
+    let hue = Hue::new(make_color_table(color), 1, 2, name.to_string());
+    HueGroup::new(
+        header,
+        [
+            hue.clone(),
+            hue.clone(),
+            hue.clone(),
+            hue.clone(),
+            hue.clone(),
+            hue.clone(),
+            hue.clone(),
+            hue.clone(),
+        ],
+    )
+}
+
+#[test]
+fn test_write_hue_groups() {
+    let mut buffer = vec![];
+    let mut writer = HueWriter::from_writable(Cursor::new(&mut buffer));
+    writer
+        .write_hue_groups(&[make_group(5, 0, "Hoojama"), make_group(6, 1, "Llama")])
+        .unwrap();
+    writer
+        .write_hue(
+            9,
+            &Hue::new(make_color_table(3), 7, 8, "Alpaca".to_string()),
+        )
+        .unwrap();
+
+    let mut reader = HueReader::from_readable(Cursor::new(buffer));
+    let first = reader.read_hue_group(0).unwrap();
+    let second = reader.read_hue_group(1).unwrap();
+    assert_eq!(first, make_group(5, 0, "Hoojama"));
+    assert_eq!(second.header, 6);
+    assert_eq!(second.entries[0].name, "Llama");
+    assert_eq!(second.entries[1].name, "Alpaca");
+    assert_eq!(second.entries[1].table_start, 7);
+    assert_eq!(second.entries[1].color_table[0], 3);
+    assert_eq!(second.entries[2].name, "Llama");
+}
+
+#[test]
+fn test_write_invalid_names() {
+    let mut buffer = vec![];
+    let mut writer = HueWriter::from_writable(Cursor::new(&mut buffer));
+    let long = Hue::new(
+        make_color_table(0),
+        0,
+        0,
+        "A name that is far too long".to_string(),
+    );
+    match writer.write_hue(0, &long) {
+        Err(MulWriterError::NameTooLong { max, .. }) => assert_eq!(max, 20),
+        other => panic!("Unexpected result {:?}", other),
+    }
+    let unicode = Hue::new(make_color_table(0), 0, 0, "Llamá".to_string());
+    match writer.write_hue_group(0, &make_group(0, 0, "Llamá")) {
+        Err(MulWriterError::NonAsciiName(_)) => (),
+        other => panic!("Unexpected result {:?}", other),
+    }
+    assert!(unicode.validate_name().is_err());
+    assert!(buffer.is_empty());
+}
@@ -0,0 +1,84 @@
+use crate::hue::Hue;
+use crate::hue::palette::{export_aco, export_gpl, import_aco, import_gpl};
+#[cfg(feature = "image")]
+use crate::hue::palette::{export_swatches, import_swatches};
+
+fn example_hues() -> Vec<Hue> {
+    let mut first = [0u16; 32];
+    let mut second = [0u16; 32];
+    for i in 0..32 {
+        first[i] = i as u16;
+        second[i] = ((i as u16) << 10) | ((31 - i as u16) << 5);
+    }
+    vec![
+        Hue::new(first, first[0], first[31], "Blues".to_string()),
+        Hue::new(second, second[0], second[31], "Red to green".to_string()),
+    ]
+}
+
+#[test]
+fn test_gpl_round_trip() {
+    let hues = example_hues();
+    let exported = export_gpl("Test", &hues);
+    assert!(exported.starts_with("GIMP Palette\nName: Test\n"));
+    let imported = import_gpl(&exported).unwrap();
+    assert_eq!(imported, hues);
+}
+
+#[test]
+fn test_gpl_quantises() {
+    let mut palette = "GIMP Palette\n#\n".to_string();
+    for _ in 0..32 {
+        palette.push_str("255 7 128 Untitled\n");
+    }
+    let imported = import_gpl(&palette).unwrap();
+    assert_eq!(imported.len(), 1);
+    assert_eq!(imported[0].color_table[0], (31 << 10) | 16);
+    assert_eq!(imported[0].name, "Untitled");
+}
+
+#[test]
+fn test_gpl_invalid() {
+    assert!(import_gpl("Not a palette\n").is_err());
+    assert!(import_gpl("GIMP Palette\n255 0 0\n").is_err());
+    assert!(import_gpl("GIMP Palette\n255 0 red\n").is_err());
+}
+
+#[test]
+fn test_aco_round_trip() {
+    let hues = example_hues();
+    let exported = export_aco(&hues).unwrap();
+    assert_eq!(&exported[0..4], &[0, 1, 0, 64]);
+    let imported = import_aco(&exported).unwrap();
+    assert_eq!(imported, hues);
+
+    //The swatch count has to fit in a u16
+    let too_many = vec![hues[0].clone(); 2048];
+    assert!(export_aco(&too_many).is_err());
+    assert!(export_aco(&too_many[..2047]).is_ok());
+}
+
+#[test]
+fn test_aco_version_1_only() {
+    let hues = example_hues();
+    let exported = export_aco(&hues).unwrap();
+    // Version 1 block is a 4 byte header, then 10 bytes per color
+    let imported = import_aco(&exported[0..4 + 64 * 10]).unwrap();
+    assert_eq!(imported.len(), 2);
+    assert_eq!(imported[1].color_table, hues[1].color_table);
+    assert_eq!(imported[1].name, "");
+}
+
+#[cfg(feature = "image")]
+#[test]
+fn test_swatch_round_trip() {
+    let hues = example_hues();
+    let image = export_swatches(&hues, 4);
+    assert_eq!(image.width(), 128);
+    assert_eq!(image.height(), 8);
+    let imported = import_swatches(&image, 4).unwrap();
+    assert_eq!(imported.len(), 2);
+    assert_eq!(imported[0].color_table, hues[0].color_table);
+    assert_eq!(imported[1].color_table, hues[1].color_table);
+    assert!(import_swatches(&image, 3).is_err());
+}
@@ -0,0 +1,72 @@
+use crate::color::Color;
+use crate::hue::ramp::{BrightnessCurve, RampOptions, generate_color_table, generate_hue};
+
+fn brightness(color: u16) -> u8 {
+    let (r, g, b, _a) = color.to_rgba();
+    r.max(g).max(b)
+}
+
+#[test]
+fn test_greyscale_ramp() {
+    let options = RampOptions {
+        min_brightness: 0.0,
+        ..RampOptions::default()
+    };
+    let table = generate_color_table(&[], &options);
+    assert_eq!(table[0], 0);
+    assert_eq!(table[31], 0x7FFF);
+    for color in table.iter() {
+        let (r, g, b, _a) = color.to_rgba();
+        assert_eq!(r, g);
+        assert_eq!(g, b);
+    }
+}
+
+#[test]
+fn test_single_anchor_ramp() {
+    let table = generate_color_table(&[(255, 0, 0)], &RampOptions::default());
+    for pair in table.windows(2) {
+        assert!(brightness(pair[0]) <= brightness(pair[1]));
+    }
+    for color in table.iter() {
+        let (r, g, b, _a) = color.to_rgba();
+        assert_eq!(g, 0);
+        assert_eq!(b, 0);
+        assert!(r > 0);
+    }
+}
+
+#[test]
+fn test_anchor_brightness() {
+    let options = RampOptions {
+        curve: BrightnessCurve::Anchors,
+        ..RampOptions::default()
+    };
+    let table = generate_color_table(&[(0, 0, 64), (0, 255, 0)], &options);
+    assert_eq!(table[0], 64 >> 3);
+    assert_eq!(table[31], 31 << 5);
+}
+
+#[test]
+fn test_saturation() {
+    let options = RampOptions {
+        saturation: 0.0,
+        ..RampOptions::default()
+    };
+    let table = generate_color_table(&[(0, 128, 255)], &options);
+    let (r, g, b, _a) = table[31].to_rgba();
+    assert_eq!((r, g, b), (255, 255, 255));
+}
+
+#[test]
+fn test_generate_hue() {
+    let hue = generate_hue(
+        "Sunset",
+        &[(64, 0, 64), (255, 128, 0)],
+        &RampOptions::default(),
+    );
+    assert_eq!(hue.name, "Sunset");
+    assert_eq!(hue.table_start, hue.color_table[0]);
+    assert_eq!(hue.table_end, hue.color_table[31]);
+    assert!(hue.validate_name().is_ok());
+}
@@ -80,6 +80,19 @@ fn test_read_entries_provided_by_helper() {
     }
 }
 
+#[test]
+fn test_read_part() {
+    let mut mul_reader = fake_reader();
+    let record = mul_reader.read_part(1, 1, 2).unwrap();
+    assert_eq!(record.start, 1);
+    assert_eq!(record.length, 4);
+    assert_eq!(record.data, vec![0xbe, 0xad]);
+    //Reads stop at the end of the entry
+    assert_eq!(mul_reader.read_part(1, 3, 10).unwrap().data, vec![0xde]);
+    assert_eq!(mul_reader.read_part(1, 4, 10).unwrap().data.len(), 0);
+    assert!(mul_reader.read_part(1, 5, 1).is_err());
+}
+
 #[test]
 fn test_read_impossible_entry() {
     let mut mul_reader = fake_reader();
@@ -112,3 +125,24 @@ fn test_write_simple_mul() {
         Err(message) => panic!("{}", message),
     }
 }
+
+#[test]
+fn test_write_index() {
+    let mut idx = vec![];
+    let mut mul = vec![];
+    let mut mul_writer = MulWriter::from_writables(Cursor::new(&mut idx), Cursor::new(&mut mul));
+    mul_writer.write(2, &[1, 2, 3], Some(4), Some(5)).unwrap();
+    mul_writer.write(0, &[6], None, None).unwrap();
+    mul_writer.write(2, &[7, 8], None, Some(9)).unwrap();
+    mul_writer.delete(0).unwrap();
+    assert_eq!(idx.len(), 36);
+
+    let mut mul_reader = MulReader::from_readables(Cursor::new(idx), Cursor::new(mul));
+    assert_eq!(mul_reader.entry_count().unwrap(), 3);
+    assert!(mul_reader.read(0).is_err());
+    assert!(mul_reader.read(1).is_err());
+    let record = mul_reader.read(2).unwrap();
+    assert_eq!(record.data, vec![7, 8]);
+    assert_eq!(record.opt1, 0);
+    assert_eq!(record.opt2, 9);
+}
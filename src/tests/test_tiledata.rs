@@ -1,11 +1,15 @@
-use crate::tiledata::{Flags, TileDataReader};
+use crate::error::MulWriterError;
+use crate::tiledata::{
+    Article, CombatClass, Flags, Layer, StaticTileData, TileDataLayout, TileDataReader,
+    TileDataWriter,
+};
 use byteorder::{LittleEndian, WriteBytesExt};
 use std::ffi::CString;
 use std::io::{Cursor, Result, Write};
 
 fn raw_map_tiledata() -> Result<Vec<u8>> {
     let mut data = Cursor::new(vec![]);
-    data.write_u32::<LittleEndian>((Flags::BackgroundFlag | Flags::ImpassableFlag).bits())?; // Flags
+    data.write_u32::<LittleEndian>((Flags::BackgroundFlag | Flags::ImpassableFlag).bits() as u32)?; // Flags
     data.write_u16::<LittleEndian>(0x1)?; // Texture ID
     data.write_all(CString::new("A tile").unwrap().as_bytes_with_nul())?;
     data.write_all(&[0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0])?;
@@ -27,7 +31,7 @@ fn raw_map_group() -> Result<Vec<u8>> {
 fn raw_static_tiledata() -> Result<Vec<u8>> {
     let mut data = Cursor::new(vec![]);
     data.write_u32::<LittleEndian>(
-        (Flags::PrefixAFlag | Flags::StackableFlag | Flags::AnimatedFlag).bits(),
+        (Flags::PrefixAFlag | Flags::StackableFlag | Flags::AnimatedFlag).bits() as u32,
     )?; // Flags
     data.write_u8(0x1)?; // Weight
     data.write_u8(0x0)?; // Quality
@@ -69,7 +73,7 @@ fn raw_mul() -> Result<Vec<u8>> {
 #[test]
 fn test_read_map_tiledata() {
     let data = Cursor::new(raw_mul().unwrap());
-    let mut reader = TileDataReader::from_readable(data);
+    let mut reader = TileDataReader::from_readable(data).unwrap();
     let map_tile = reader.read_map_tile_data(33).unwrap();
     assert_eq!(
         map_tile.flags,
@@ -81,7 +85,7 @@ fn test_read_map_tiledata() {
 #[test]
 fn test_read_static_tiledata() {
     let data = Cursor::new(raw_mul().unwrap());
-    let mut reader = TileDataReader::from_readable(data);
+    let mut reader = TileDataReader::from_readable(data).unwrap();
     let static_tile = reader.read_static_tile_data(0).unwrap();
     assert_eq!(
         static_tile.flags,
@@ -94,3 +98,314 @@ fn test_read_static_tiledata() {
     assert_eq!(static_tile.hue, 7);
     assert_eq!(static_tile.height_capacity, 0);
 }
+
+fn raw_hs_map_tiledata() -> Result<Vec<u8>> {
+    let mut data = Cursor::new(vec![]);
+    data.write_u64::<LittleEndian>((Flags::WetFlag | Flags::NoShadowFlag).bits())?; // Flags
+    data.write_u16::<LittleEndian>(0x2)?; // Texture ID
+    data.write_all(b"water\0\0\0\0\0\0\0\0\0\0\0\0\0\0\0")?;
+    let unwrapped = data.into_inner();
+    assert_eq!(unwrapped.len(), 30);
+    Ok(unwrapped)
+}
+
+fn raw_hs_static_tiledata() -> Result<Vec<u8>> {
+    let mut data = Cursor::new(vec![]);
+    data.write_u64::<LittleEndian>((Flags::WearableFlag | Flags::PlayAnimOnceFlag).bits())?; // Flags
+    data.write_u8(0x2)?; // Weight
+    data.write_u8(0x5)?; // Quality
+    data.write_u16::<LittleEndian>(0x0)?; // Unknown
+    data.write_u8(0x0)?; // Unknown
+    data.write_u8(0x1)?; // Quantity
+    data.write_u16::<LittleEndian>(0x1234)?; // Anim id
+    data.write_u8(0x0)?; // Unknown
+    data.write_u8(0x0)?; // Hue
+    data.write_u16::<LittleEndian>(0x0)?; // Unknown
+    data.write_u8(0x1)?; // Height
+    // A name filling the whole field, with no terminator
+    data.write_all(b"a very fancy hat!!!!")?;
+    let unwrapped = data.into_inner();
+    assert_eq!(unwrapped.len(), 41);
+    Ok(unwrapped)
+}
+
+fn raw_hs_mul(static_groups: u32) -> Result<Vec<u8>> {
+    let mut data = Cursor::new(vec![]);
+    let map_tile = raw_hs_map_tiledata()?;
+    for _ in 0..512 {
+        data.write_u32::<LittleEndian>(0x0)?; // Unknown
+        for _ in 0..32 {
+            data.write_all(&map_tile)?;
+        }
+    }
+    let static_tile = raw_hs_static_tiledata()?;
+    for _ in 0..static_groups {
+        data.write_u32::<LittleEndian>(0x0)?; // Unknown
+        for _ in 0..32 {
+            data.write_all(&static_tile)?;
+        }
+    }
+    Ok(data.into_inner())
+}
+
+#[test]
+fn test_detect_layout() {
+    let classic = TileDataReader::from_readable(Cursor::new(raw_mul().unwrap())).unwrap();
+    assert_eq!(classic.layout(), TileDataLayout::Classic);
+    assert_eq!(classic.map_tile_count(), 0x4000);
+    assert_eq!(classic.static_tile_count(), 32);
+
+    let high_seas = TileDataReader::from_readable(Cursor::new(raw_hs_mul(3).unwrap())).unwrap();
+    assert_eq!(high_seas.layout(), TileDataLayout::HighSeas);
+    assert_eq!(high_seas.static_tile_count(), 96);
+
+    assert_eq!(TileDataLayout::detect(3188736), TileDataLayout::HighSeas);
+    assert_eq!(TileDataLayout::detect(1644544), TileDataLayout::Classic);
+}
+
+#[test]
+fn test_read_hs_tiledata() {
+    let mut reader = TileDataReader::from_readable(Cursor::new(raw_hs_mul(2).unwrap())).unwrap();
+    let map_tile = reader.read_map_tile_data(0x3FFF).unwrap();
+    assert_eq!(map_tile.flags, Flags::WetFlag | Flags::NoShadowFlag);
+    assert_eq!(map_tile.texture_id, 2);
+    assert_eq!(map_tile.name, "water");
+
+    let static_tile = reader.read_static_tile_data(63).unwrap();
+    assert_eq!(
+        static_tile.flags,
+        Flags::WearableFlag | Flags::PlayAnimOnceFlag
+    );
+    assert_eq!(static_tile.weight, 2);
+    assert_eq!(static_tile.quality_layer_light_id, 5);
+    assert_eq!(static_tile.anim_id, 0x1234);
+    assert_eq!(static_tile.height_capacity, 1);
+    assert_eq!(static_tile.name, "a very fancy hat!!!!");
+}
+
+#[test]
+fn test_read_out_of_bounds() {
+    let mut reader = TileDataReader::from_readable(Cursor::new(raw_mul().unwrap())).unwrap();
+    assert!(reader.read_map_tile_data(0x4000).is_err());
+    assert!(reader.read_static_tile_data(32).is_err());
+}
+
+fn raw_unknown_map_tiledata() -> Result<Vec<u8>> {
+    let mut data = Cursor::new(vec![]);
+    data.write_u32::<LittleEndian>(Flags::WetFlag.bits() as u32)?; // Flags
+    data.write_u16::<LittleEndian>(0x1)?; // Texture ID
+    // A name that isn't UTF-8, with leftovers from a longer name after the terminator
+    data.write_all(b"caf\xE9\0\xFFold name\0\0\0\0\0\0")?;
+    let unwrapped = data.into_inner();
+    assert_eq!(unwrapped.len(), 26);
+    Ok(unwrapped)
+}
+
+fn raw_unknown_static_tiledata() -> Result<Vec<u8>> {
+    let mut data = Cursor::new(vec![]);
+    data.write_u32::<LittleEndian>(Flags::StackableFlag.bits() as u32)?; // Flags
+    data.write_u8(0x1)?; // Weight
+    data.write_u8(0x0)?; // Quality
+    data.write_u16::<LittleEndian>(0x1234)?; // Unknown
+    data.write_u8(0x3)?; // Unknown
+    data.write_u8(0x5)?; // Quantity
+    data.write_u16::<LittleEndian>(0x0)?; // Anim id
+    data.write_u8(0x9)?; // Unknown
+    data.write_u8(0x7)?; // Hue
+    data.write_u16::<LittleEndian>(0xBEEF)?; // Unknown
+    data.write_u8(0x0)?; // Height
+    data.write_all(b"gold\0en coins\0\0\0\0\0\0\0")?;
+    let unwrapped = data.into_inner();
+    assert_eq!(unwrapped.len(), 37);
+    Ok(unwrapped)
+}
+
+fn raw_unknown_mul() -> Result<Vec<u8>> {
+    let mut data = Cursor::new(vec![]);
+    let map_tile = raw_unknown_map_tiledata()?;
+    for _ in 0..512 {
+        data.write_u32::<LittleEndian>(0xDEADBEEF)?; // Unknown
+        for _ in 0..32 {
+            data.write_all(&map_tile)?;
+        }
+    }
+    let static_tile = raw_unknown_static_tiledata()?;
+    data.write_u32::<LittleEndian>(0xCAFE)?; // Unknown
+    for _ in 0..32 {
+        data.write_all(&static_tile)?;
+    }
+    Ok(data.into_inner())
+}
+
+fn round_trip(raw: Vec<u8>) -> Vec<u8> {
+    let mut reader = TileDataReader::from_readable(Cursor::new(raw)).unwrap();
+    let map_groups: Vec<_> = (0..512)
+        .map(|group| reader.read_map_tile_group(group).unwrap())
+        .collect();
+    let static_groups: Vec<_> = (0..reader.static_tile_count() / 32)
+        .map(|group| reader.read_static_tile_group(group).unwrap())
+        .collect();
+
+    let mut output = vec![];
+    let mut writer = TileDataWriter::from_writable(Cursor::new(&mut output), reader.layout());
+    writer.write_all(&map_groups, &static_groups).unwrap();
+    output
+}
+
+#[test]
+fn test_round_trip() {
+    let raw = raw_mul().unwrap();
+    assert_eq!(round_trip(raw.clone()), raw);
+    let raw = raw_hs_mul(2).unwrap();
+    assert_eq!(round_trip(raw.clone()), raw);
+    let raw = raw_unknown_mul().unwrap();
+    assert_eq!(round_trip(raw.clone()), raw);
+}
+
+#[test]
+fn test_read_groups() {
+    let mut reader =
+        TileDataReader::from_readable(Cursor::new(raw_unknown_mul().unwrap())).unwrap();
+    let map_group = reader.read_map_tile_group(511).unwrap();
+    assert_eq!(map_group.header, 0xDEADBEEF);
+    assert_eq!(map_group.entries.len(), 32);
+    assert_eq!(map_group.entries[0].name, "caf\u{FFFD}");
+    let static_group = reader.read_static_tile_group(0).unwrap();
+    assert_eq!(static_group.header, 0xCAFE);
+    let static_tile = &static_group.entries[31];
+    assert_eq!(
+        (
+            static_tile.unknown,
+            static_tile.unknown1,
+            static_tile.unknown2,
+            static_tile.unknown3
+        ),
+        (0x1234, 3, 9, 0xBEEF)
+    );
+    assert_eq!(static_tile.name, "gold");
+    assert_eq!(&static_tile.raw_name[..8], b"gold\0en ");
+    assert!(reader.read_static_tile_group(1).is_err());
+}
+
+#[test]
+fn test_edit_in_place() {
+    let mut raw = raw_unknown_mul().unwrap();
+    let mut reader = TileDataReader::from_readable(Cursor::new(raw.clone())).unwrap();
+    let mut static_tile = reader.read_static_tile_data(5).unwrap();
+    static_tile.weight = 99;
+    static_tile.name = "pile of gold".to_string();
+    let mut map_tile = reader.read_map_tile_data(40).unwrap();
+    map_tile.flags |= Flags::WetFlag;
+
+    let mut writer = TileDataWriter::from_writable(Cursor::new(&mut raw), TileDataLayout::Classic);
+    writer.write_static_tile_data(5, &static_tile).unwrap();
+    writer.write_map_tile_data(40, &map_tile).unwrap();
+
+    let mut reader = TileDataReader::from_readable(Cursor::new(raw)).unwrap();
+    //Renaming a tile replaces the whole stored name
+    let mut renamed = static_tile.clone();
+    renamed.raw_name = *b"pile of gold\0\0\0\0\0\0\0\0";
+    assert_eq!(reader.read_static_tile_data(5).unwrap(), renamed);
+    assert_eq!(reader.read_static_tile_data(6).unwrap().weight, 1);
+    assert_eq!(reader.read_map_tile_data(40).unwrap(), map_tile);
+    assert_eq!(reader.read_map_tile_group(1).unwrap().header, 0xDEADBEEF);
+}
+
+#[test]
+fn test_write_invalid() {
+    let mut reader = TileDataReader::from_readable(Cursor::new(raw_mul().unwrap())).unwrap();
+    let mut static_tile = reader.read_static_tile_data(0).unwrap();
+    static_tile.name = "a name that is far too long".to_string();
+    let mut output = vec![];
+    let mut writer =
+        TileDataWriter::from_writable(Cursor::new(&mut output), TileDataLayout::Classic);
+    match writer.write_static_tile_data(0, &static_tile) {
+        Err(MulWriterError::NameTooLong { max, .. }) => assert_eq!(max, 20),
+        other => panic!("Unexpected result {:?}", other),
+    }
+    static_tile.name = "café".to_string();
+    match writer.write_static_tile_data(0, &static_tile) {
+        Err(MulWriterError::NonAsciiName(name)) => assert_eq!(name, "café"),
+        other => panic!("Unexpected result {:?}", other),
+    }
+    static_tile.name = "apple".to_string();
+    match writer.write_static_tile_data(0x10000, &static_tile) {
+        Err(MulWriterError::IndexOutOfBounds(idx)) => assert_eq!(idx, 0x10000),
+        other => panic!("Unexpected result {:?}", other),
+    }
+    match writer.write_all(&[], &[]) {
+        Err(MulWriterError::UnexpectedSize { expected, .. }) => assert_eq!(expected, 512),
+        other => panic!("Unexpected result {:?}", other),
+    }
+}
+
+fn example_static(flags: Flags, quality: u8, quantity: u8, height: u8) -> StaticTileData {
+    StaticTileData {
+        flags,
+        weight: 1,
+        quality_layer_light_id: quality,
+        unknown: 0,
+        unknown1: 0,
+        quantity_weapon_class_armor_class: quantity,
+        anim_id: 0,
+        unknown2: 0,
+        hue: 0,
+        unknown3: 0,
+        height_capacity: height,
+        name: "apple".to_string(),
+        raw_name: [0; 20],
+    }
+}
+
+#[test]
+fn test_typed_accessors() {
+    let plain = example_static(Flags::empty(), 4, 5, 6);
+    assert_eq!(plain.quality(), Some(4));
+    assert_eq!(plain.layer(), None);
+    assert_eq!(plain.light_id(), None);
+    assert_eq!(plain.quantity(), Some(5));
+    assert_eq!(plain.combat_class(), None);
+    assert_eq!(plain.height(), Some(6));
+    assert_eq!(plain.capacity(), None);
+    assert_eq!(plain.article(), None);
+    assert_eq!(plain.name_with_article(), "apple");
+
+    let helmet = example_static(
+        Flags::WearableFlag | Flags::ArmorFlag | Flags::PrefixAFlag,
+        6,
+        3,
+        1,
+    );
+    assert_eq!(helmet.layer(), Some(Layer::Helm));
+    assert_eq!(helmet.quality(), None);
+    assert_eq!(helmet.combat_class(), Some(CombatClass::Armor(3)));
+    assert_eq!(helmet.quantity(), None);
+    assert_eq!(helmet.article(), Some(Article::A));
+
+    let sword = example_static(Flags::WeaponFlag | Flags::WearableFlag, 1, 2, 0);
+    assert_eq!(sword.layer(), Some(Layer::OneHanded));
+    assert_eq!(sword.combat_class(), Some(CombatClass::Weapon(2)));
+
+    let lamp = example_static(Flags::LightSourceFlag | Flags::PrefixAnFlag, 9, 0, 0);
+    assert_eq!(lamp.light_id(), Some(9));
+    assert_eq!(lamp.quality(), None);
+    assert_eq!(lamp.name_with_article(), "an apple");
+
+    let crate_tile = example_static(Flags::ContainerFlag, 0, 0, 125);
+    assert_eq!(crate_tile.capacity(), Some(125));
+    assert_eq!(crate_tile.height(), None);
+
+    assert_eq!(Layer::from_id(0), None);
+    assert_eq!(Layer::from_id(29), Some(Layer::Bank));
+    assert_eq!(Layer::Bank.id(), 29);
+}
+
+#[test]
+fn test_read_all() {
+    let mut reader = TileDataReader::from_readable(Cursor::new(raw_mul().unwrap())).unwrap();
+    let tiledata = reader.read_all().unwrap();
+    assert_eq!(tiledata.map_tiles().len(), 0x4000);
+    assert_eq!(tiledata.static_tiles().len(), 32);
+    assert_eq!(tiledata.static_tile(31).unwrap().name, "stack of coins");
+    assert!(tiledata.static_tile(32).is_none());
+}
@@ -0,0 +1,153 @@
+use crate::error::MulReaderError;
+use crate::tiledata::interchange::{
+    map_tiles_from_csv, map_tiles_to_csv, static_tiles_from_csv, static_tiles_to_csv,
+};
+#[cfg(feature = "json")]
+use crate::tiledata::interchange::{
+    map_tiles_from_json, map_tiles_to_json, static_tiles_from_json, static_tiles_to_json,
+};
+use crate::tiledata::{Flags, MapTileData, StaticTileData};
+
+fn example_map_tiles() -> Vec<MapTileData> {
+    vec![
+        MapTileData {
+            flags: Flags::empty(),
+            texture_id: 0,
+            name: "grass".to_string(),
+            raw_name: [0; 20],
+        },
+        MapTileData {
+            flags: Flags::WetFlag | Flags::NoShadowFlag,
+            texture_id: 12,
+            name: "water, deep".to_string(),
+            raw_name: [0; 20],
+        },
+    ]
+}
+
+fn example_static_tiles() -> Vec<StaticTileData> {
+    vec![StaticTileData {
+        flags: Flags::WearableFlag | Flags::PrefixAFlag,
+        weight: 5,
+        quality_layer_light_id: 6,
+        unknown: 300,
+        unknown1: 1,
+        quantity_weapon_class_armor_class: 2,
+        anim_id: 0x1F,
+        unknown2: 3,
+        hue: 4,
+        unknown3: 500,
+        height_capacity: 7,
+        name: "\"great\" helm".to_string(),
+        raw_name: [0; 20],
+    }]
+}
+
+#[test]
+fn test_csv_round_trip() {
+    let map_tiles = example_map_tiles();
+    let csv = map_tiles_to_csv(&map_tiles);
+    assert!(csv.starts_with("id,name,texture_id,BackgroundFlag,"));
+    assert!(csv.contains("\"water, deep\""));
+    let imported = map_tiles_from_csv(&csv).unwrap();
+    assert_eq!(
+        imported,
+        vec![(0, map_tiles[0].clone()), (1, map_tiles[1].clone())]
+    );
+
+    let static_tiles = example_static_tiles();
+    let csv = static_tiles_to_csv(&static_tiles);
+    let imported = static_tiles_from_csv(&csv).unwrap();
+    assert_eq!(imported, vec![(0, static_tiles[0].clone())]);
+}
+
+#[test]
+fn test_csv_partial_import() {
+    let csv = "id,name,weight,quality_layer_light_id,quantity_weapon_class_armor_class,anim_id,hue,height_capacity,WallFlag\n\
+               0x10,,,,,,,,\n";
+    assert!(static_tiles_from_csv(csv).is_err());
+
+    let csv = "id,name,weight,quality_layer_light_id,quantity_weapon_class_armor_class,anim_id,hue,height_capacity,WallFlag,ImpassableFlag\n\
+               400,stone wall,255,0,0,0,0,20,1,true\n\n";
+    let imported = static_tiles_from_csv(csv).unwrap();
+    assert_eq!(imported.len(), 1);
+    let (id, tile) = &imported[0];
+    assert_eq!(*id, 400);
+    assert_eq!(tile.flags, Flags::WallFlag | Flags::ImpassableFlag);
+    assert_eq!(tile.height_capacity, 20);
+    assert_eq!(tile.unknown3, 0);
+}
+
+#[test]
+fn test_csv_errors() {
+    let csv = "id,name,texture_id\n0,grass,0\n1,sand,70000\n";
+    match map_tiles_from_csv(csv) {
+        Err(MulReaderError::InvalidRow { row, .. }) => assert_eq!(row, 3),
+        other => panic!("Unexpected result {:?}", other),
+    }
+
+    let csv = "id,name,texture_id\n0,a name that is much too long,0\n";
+    match map_tiles_from_csv(csv) {
+        Err(MulReaderError::InvalidRow { row, .. }) => assert_eq!(row, 2),
+        other => panic!("Unexpected result {:?}", other),
+    }
+
+    let csv = "id,name,texture_id\n0,grass,0\n1,café,0\n";
+    match map_tiles_from_csv(csv) {
+        Err(MulReaderError::InvalidRow { row, .. }) => assert_eq!(row, 3),
+        other => panic!("Unexpected result {:?}", other),
+    }
+
+    let csv = "id,name,texture_id,FlyingFlag\n0,grass,0,1\n";
+    match map_tiles_from_csv(csv) {
+        Err(MulReaderError::InvalidRow { row, .. }) => assert_eq!(row, 1),
+        other => panic!("Unexpected result {:?}", other),
+    }
+
+    //Rows are counted by line, so quoted line breaks move later rows down
+    let csv = "id,name,texture_id\n0,\"long\ngrass\",0\n\n1,sand,70000\n";
+    match map_tiles_from_csv(csv) {
+        Err(MulReaderError::InvalidRow { row, .. }) => assert_eq!(row, 5),
+        other => panic!("Unexpected result {:?}", other),
+    }
+
+    let csv = "id,name,texture_id,WetFlag\n0,grass,0,maybe\n";
+    assert!(map_tiles_from_csv(csv).is_err());
+    let csv = "id,name,texture_id\n0,grass\n";
+    assert!(map_tiles_from_csv(csv).is_err());
+}
+
+#[cfg(feature = "json")]
+#[test]
+fn test_json_round_trip() {
+    let map_tiles = example_map_tiles();
+    let json = map_tiles_to_json(&map_tiles);
+    assert!(json.contains("\"WetFlag\": true"));
+    let imported = map_tiles_from_json(&json).unwrap();
+    assert_eq!(
+        imported,
+        vec![(0, map_tiles[0].clone()), (1, map_tiles[1].clone())]
+    );
+
+    let static_tiles = example_static_tiles();
+    let json = static_tiles_to_json(&static_tiles);
+    let imported = static_tiles_from_json(&json).unwrap();
+    assert_eq!(imported, vec![(0, static_tiles[0].clone())]);
+}
+
+#[cfg(feature = "json")]
+#[test]
+fn test_json_errors() {
+    let json = r#"[{"id": 0, "name": "grass", "texture_id": 0}, {"id": 1, "name": "sand"}]"#;
+    match map_tiles_from_json(json) {
+        Err(MulReaderError::InvalidRow { row, .. }) => assert_eq!(row, 1),
+        other => panic!("Unexpected result {:?}", other),
+    }
+    let json = r#"[{"id": 0, "name": "caf\u00e9", "texture_id": 0}]"#;
+    match map_tiles_from_json(json) {
+        Err(MulReaderError::InvalidRow { row, .. }) => assert_eq!(row, 0),
+        other => panic!("Unexpected result {:?}", other),
+    }
+    assert!(map_tiles_from_json("{}").is_err());
+    assert!(map_tiles_from_json("[1]").is_err());
+}
@@ -0,0 +1,103 @@
+#[cfg(feature = "regex")]
+use regex::Regex;
+
+use crate::tiledata::{Flags, Layer, MapTileData, StaticTileData, TileData};
+
+fn example_static(name: &str, flags: Flags, quality: u8, anim_id: u16) -> StaticTileData {
+    StaticTileData {
+        flags,
+        weight: 1,
+        quality_layer_light_id: quality,
+        unknown: 0,
+        unknown1: 0,
+        quantity_weapon_class_armor_class: 0,
+        anim_id,
+        unknown2: 0,
+        hue: 0,
+        unknown3: 0,
+        height_capacity: 0,
+        name: name.to_string(),
+        raw_name: [0; 20],
+    }
+}
+
+fn example_tiledata() -> TileData {
+    let map_tiles = vec![
+        MapTileData {
+            flags: Flags::empty(),
+            texture_id: 0,
+            name: "grass".to_string(),
+            raw_name: [0; 20],
+        },
+        MapTileData {
+            flags: Flags::WetFlag | Flags::ImpassableFlag,
+            texture_id: 1,
+            name: "water".to_string(),
+            raw_name: [0; 20],
+        },
+    ];
+    let static_tiles = vec![
+        example_static("stone wall", Flags::WallFlag | Flags::ImpassableFlag, 0, 0),
+        example_static(
+            "plate helm",
+            Flags::WearableFlag | Flags::ArmorFlag,
+            6,
+            0x1F,
+        ),
+        example_static("Wooden Wall", Flags::WallFlag, 0, 0),
+        example_static("bascinet", Flags::WearableFlag, 6, 0x1F),
+        example_static("sandals", Flags::WearableFlag, 3, 0x20),
+    ];
+    TileData::new(map_tiles, static_tiles)
+}
+
+#[test]
+fn test_lookup() {
+    let tiledata = example_tiledata();
+    assert_eq!(tiledata.map_tile(1).unwrap().name, "water");
+    assert!(tiledata.map_tile(2).is_none());
+    assert_eq!(tiledata.static_tile(4).unwrap().name, "sandals");
+    assert!(tiledata.static_tile(5).is_none());
+}
+
+#[test]
+fn test_find_by_name() {
+    let tiledata = example_tiledata();
+    assert_eq!(tiledata.find_static_tiles_by_name("wall"), vec![0, 2]);
+    assert_eq!(tiledata.find_map_tiles_by_name("WAT"), vec![1]);
+    assert!(tiledata.find_static_tiles_by_name("sword").is_empty());
+}
+
+#[cfg(feature = "regex")]
+#[test]
+fn test_find_by_regex() {
+    let tiledata = example_tiledata();
+    let pattern = Regex::new("^[a-z]+ (helm|wall)$").unwrap();
+    assert_eq!(tiledata.find_static_tiles_by_regex(&pattern), vec![0, 1]);
+    let pattern = Regex::new("^g").unwrap();
+    assert_eq!(tiledata.find_map_tiles_by_regex(&pattern), vec![0]);
+}
+
+#[test]
+fn test_find_by_flags() {
+    let tiledata = example_tiledata();
+    assert_eq!(
+        tiledata.static_tiles_with_flags(Flags::WallFlag | Flags::ImpassableFlag),
+        vec![0]
+    );
+    assert_eq!(
+        tiledata.static_tiles_with_flags(Flags::WallFlag),
+        vec![0, 2]
+    );
+    assert_eq!(tiledata.map_tiles_with_flags(Flags::WetFlag), vec![1]);
+    assert_eq!(tiledata.wearables_on_layer(Layer::Helm), vec![1, 3]);
+    assert_eq!(tiledata.wearables_on_layer(Layer::Shoes), vec![4]);
+}
+
+#[test]
+fn test_statics_for_anim() {
+    let tiledata = example_tiledata();
+    assert_eq!(tiledata.statics_for_anim(0x1F), &[1, 3]);
+    assert_eq!(tiledata.statics_for_anim(0x20), &[4]);
+    assert!(tiledata.statics_for_anim(0).is_empty());
+}
@@ -0,0 +1,443 @@
+//! Methods for moving tiledata in and out of spreadsheet-friendly formats
+//!
+//! Map and static tiles are exported as one row per id. Every field gets its own column, named
+//! after the struct field, followed by one column per flag holding `1` or `0`.
+//!
+//! On import, flag columns may be left out or blank, and rows may cover any subset of ids.
+//! Errors point at the offending row - for CSV this is the line of the file the record starts on,
+//! with the header on line 1, and for JSON it is the position in the array, counting from 0.
+//!
+//! JSON support requires the `json` feature.
+use std::collections::HashMap;
+use std::str::FromStr;
+
+use super::{Flags, MapTileData, NAME_SIZE, StaticTileData};
+use crate::error::{MulReaderError, MulReaderResult};
+
+const MAP_COLUMNS: [&str; 3] = ["id", "name", "texture_id"];
+const STATIC_COLUMNS: [&str; 12] = [
+    "id",
+    "name",
+    "weight",
+    "quality_layer_light_id",
+    "quantity_weapon_class_armor_class",
+    "anim_id",
+    "hue",
+    "height_capacity",
+    "unknown",
+    "unknown1",
+    "unknown2",
+    "unknown3",
+];
+
+fn header(columns: &[&str]) -> Vec<String> {
+    columns
+        .iter()
+        .map(|column| column.to_string())
+        .chain(Flags::all().iter_names().map(|(name, _)| name.to_string()))
+        .collect()
+}
+
+fn with_flags(mut cells: Vec<String>, flags: Flags) -> Vec<String> {
+    for (_, flag) in Flags::all().iter_names() {
+        cells.push(if flags.contains(flag) { "1" } else { "0" }.to_string());
+    }
+    cells
+}
+
+fn map_tile_row(id: usize, tile: &MapTileData) -> Vec<String> {
+    with_flags(
+        vec![
+            id.to_string(),
+            tile.name.clone(),
+            tile.texture_id.to_string(),
+        ],
+        tile.flags,
+    )
+}
+
+fn static_tile_row(id: usize, tile: &StaticTileData) -> Vec<String> {
+    with_flags(
+        vec![
+            id.to_string(),
+            tile.name.clone(),
+            tile.weight.to_string(),
+            tile.quality_layer_light_id.to_string(),
+            tile.quantity_weapon_class_armor_class.to_string(),
+            tile.anim_id.to_string(),
+            tile.hue.to_string(),
+            tile.height_capacity.to_string(),
+            tile.unknown.to_string(),
+            tile.unknown1.to_string(),
+            tile.unknown2.to_string(),
+            tile.unknown3.to_string(),
+        ],
+        tile.flags,
+    )
+}
+
+/// A single imported row, keyed by column name
+struct Row {
+    number: u32,
+    cells: HashMap<String, String>,
+}
+
+impl Row {
+    fn error(&self, message: String) -> MulReaderError {
+        MulReaderError::InvalidRow {
+            row: self.number,
+            message,
+        }
+    }
+
+    fn get(&self, column: &str) -> MulReaderResult<&str> {
+        self.cells
+            .get(column)
+            .map(|cell| cell.as_str())
+            .ok_or_else(|| self.error(format!("Missing column {}", column)))
+    }
+
+    fn parse<T: FromStr>(&self, column: &str) -> MulReaderResult<T> {
+        let cell = self.get(column)?;
+        cell.trim()
+            .parse::<T>()
+            .map_err(|_| self.error(format!("Invalid value {:?} for {}", cell, column)))
+    }
+
+    fn parse_or_default<T: FromStr + Default>(&self, column: &str) -> MulReaderResult<T> {
+        match self.cells.get(column) {
+            Some(cell) if !cell.trim().is_empty() => self.parse(column),
+            _ => Ok(T::default()),
+        }
+    }
+
+    fn name(&self) -> MulReaderResult<String> {
+        let name = self.get("name")?;
+        if !name.is_ascii() {
+            return Err(self.error(format!("Name {:?} contains non-ASCII characters", name)));
+        }
+        if name.len() > NAME_SIZE {
+            return Err(self.error(format!(
+                "Name {:?} is longer than {} bytes",
+                name, NAME_SIZE
+            )));
+        }
+        Ok(name.to_string())
+    }
+
+    fn flags(&self) -> MulReaderResult<Flags> {
+        let mut flags = Flags::empty();
+        for (name, flag) in Flags::all().iter_names() {
+            match self.cells.get(name).map(|cell| cell.trim()) {
+                None | Some("") | Some("0") | Some("false") => (),
+                Some("1") | Some("true") => flags |= flag,
+                Some(other) => {
+                    return Err(self.error(format!("Invalid value {:?} for {}", other, name)));
+                }
+            }
+        }
+        Ok(flags)
+    }
+
+    fn to_map_tile(&self) -> MulReaderResult<(u32, MapTileData)> {
+        Ok((
+            self.parse("id")?,
+            MapTileData {
+                flags: self.flags()?,
+                texture_id: self.parse("texture_id")?,
+                name: self.name()?,
+                raw_name: [0; NAME_SIZE],
+            },
+        ))
+    }
+
+    fn to_static_tile(&self) -> MulReaderResult<(u32, StaticTileData)> {
+        Ok((
+            self.parse("id")?,
+            StaticTileData {
+                flags: self.flags()?,
+                weight: self.parse("weight")?,
+                quality_layer_light_id: self.parse("quality_layer_light_id")?,
+                unknown: self.parse_or_default("unknown")?,
+                unknown1: self.parse_or_default("unknown1")?,
+                quantity_weapon_class_armor_class: self
+                    .parse("quantity_weapon_class_armor_class")?,
+                anim_id: self.parse("anim_id")?,
+                unknown2: self.parse_or_default("unknown2")?,
+                hue: self.parse("hue")?,
+                unknown3: self.parse_or_default("unknown3")?,
+                height_capacity: self.parse("height_capacity")?,
+                name: self.name()?,
+                raw_name: [0; NAME_SIZE],
+            },
+        ))
+    }
+}
+
+fn check_columns<'a, I: Iterator<Item = &'a String>>(
+    row: u32,
+    found: I,
+    columns: &[&str],
+) -> MulReaderResult<()> {
+    let known = header(columns);
+    for column in found {
+        if !known.contains(column) {
+            return Err(MulReaderError::InvalidRow {
+                row,
+                message: format!("Unknown column {}", column),
+            });
+        }
+    }
+    Ok(())
+}
+
+fn quote_csv(cell: &str) -> String {
+    if cell.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", cell.replace('"', "\"\""))
+    } else {
+        cell.to_string()
+    }
+}
+
+fn write_csv(rows: Vec<Vec<String>>) -> String {
+    let mut out = String::new();
+    for row in rows {
+        let cells: Vec<String> = row.iter().map(|cell| quote_csv(cell)).collect();
+        out.push_str(&cells.join(","));
+        out.push_str("\r\n");
+    }
+    out
+}
+
+/// Split CSV into records, each with the line it starts on. Quoted values may span lines
+fn parse_csv(data: &str) -> MulReaderResult<Vec<(u32, Vec<String>)>> {
+    let mut records = vec![];
+    let mut record = vec![];
+    let mut cell = String::new();
+    let mut quoted = false;
+    let mut line = 1;
+    let mut start_line = 1;
+    let mut chars = data.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if quoted {
+            match c {
+                '"' if chars.peek() == Some(&'"') => {
+                    cell.push('"');
+                    chars.next();
+                }
+                '"' => quoted = false,
+                '\n' => {
+                    line += 1;
+                    cell.push(c);
+                }
+                _ => cell.push(c),
+            }
+        } else {
+            match c {
+                '"' => quoted = true,
+                ',' => record.push(std::mem::take(&mut cell)),
+                '\r' => (),
+                '\n' => {
+                    record.push(std::mem::take(&mut cell));
+                    records.push((start_line, std::mem::take(&mut record)));
+                    line += 1;
+                    start_line = line;
+                }
+                _ => cell.push(c),
+            }
+        }
+    }
+    if quoted {
+        return Err(MulReaderError::InvalidRow {
+            row: start_line,
+            message: "Unterminated quoted value".to_string(),
+        });
+    }
+    if !cell.is_empty() || !record.is_empty() {
+        record.push(cell);
+        records.push((start_line, record));
+    }
+    Ok(records)
+}
+
+fn read_csv(data: &str, columns: &[&str]) -> MulReaderResult<Vec<Row>> {
+    let mut records = parse_csv(data)?.into_iter();
+    let (header_line, header) = records.next().ok_or(MulReaderError::InvalidRow {
+        row: 1,
+        message: "Missing header".to_string(),
+    })?;
+    check_columns(header_line, header.iter(), columns)?;
+
+    let mut rows = vec![];
+    for (number, record) in records {
+        //Skip blank lines, which spreadsheets like to leave at the end
+        if record.iter().all(|cell| cell.is_empty()) {
+            continue;
+        }
+        if record.len() != header.len() {
+            return Err(MulReaderError::InvalidRow {
+                row: number,
+                message: format!("Expected {} cells, found {}", header.len(), record.len()),
+            });
+        }
+        rows.push(Row {
+            number,
+            cells: header.iter().cloned().zip(record).collect(),
+        });
+    }
+    Ok(rows)
+}
+
+/// Export map tiles as CSV, using their position in the slice as the id
+pub fn map_tiles_to_csv(tiles: &[MapTileData]) -> String {
+    let mut rows = vec![header(&MAP_COLUMNS)];
+    rows.extend(
+        tiles
+            .iter()
+            .enumerate()
+            .map(|(id, tile)| map_tile_row(id, tile)),
+    );
+    write_csv(rows)
+}
+
+/// Export static tiles as CSV, using their position in the slice as the id
+pub fn static_tiles_to_csv(tiles: &[StaticTileData]) -> String {
+    let mut rows = vec![header(&STATIC_COLUMNS)];
+    rows.extend(
+        tiles
+            .iter()
+            .enumerate()
+            .map(|(id, tile)| static_tile_row(id, tile)),
+    );
+    write_csv(rows)
+}
+
+/// Import map tiles from CSV, returning each with its id
+pub fn map_tiles_from_csv(data: &str) -> MulReaderResult<Vec<(u32, MapTileData)>> {
+    read_csv(data, &MAP_COLUMNS)?
+        .iter()
+        .map(|row| row.to_map_tile())
+        .collect()
+}
+
+/// Import static tiles from CSV, returning each with its id
+pub fn static_tiles_from_csv(data: &str) -> MulReaderResult<Vec<(u32, StaticTileData)>> {
+    read_csv(data, &STATIC_COLUMNS)?
+        .iter()
+        .map(|row| row.to_static_tile())
+        .collect()
+}
+
+#[cfg(feature = "json")]
+fn write_json(columns: &[&str], rows: Vec<Vec<String>>) -> String {
+    use serde_json::{Map, Value};
+
+    let header = header(columns);
+    let objects: Vec<Value> = rows
+        .into_iter()
+        .map(|row| {
+            let mut object = Map::new();
+            for (column, cell) in header.iter().zip(row) {
+                //Everything but the name is numeric, and flags are better read as booleans
+                let value = if column == "name" {
+                    Value::String(cell)
+                } else if column.ends_with("Flag") {
+                    Value::Bool(cell == "1")
+                } else {
+                    Value::Number(
+                        cell.parse::<u64>()
+                            .expect("Exported a non-numeric cell")
+                            .into(),
+                    )
+                };
+                object.insert(column.clone(), value);
+            }
+            Value::Object(object)
+        })
+        .collect();
+    serde_json::to_string_pretty(&Value::Array(objects)).expect("Failed to serialize JSON")
+}
+
+#[cfg(feature = "json")]
+fn read_json(data: &str, columns: &[&str]) -> MulReaderResult<Vec<Row>> {
+    use serde_json::Value;
+
+    let parsed: Value = serde_json::from_str(data)
+        .map_err(|e| MulReaderError::FailedParse(format!("Invalid JSON - {}", e)))?;
+    let array = parsed
+        .as_array()
+        .ok_or_else(|| MulReaderError::FailedParse("Expected a JSON array".to_string()))?;
+
+    let mut rows = vec![];
+    for (idx, value) in array.iter().enumerate() {
+        let number = idx as u32;
+        let object = value.as_object().ok_or(MulReaderError::InvalidRow {
+            row: number,
+            message: "Expected an object".to_string(),
+        })?;
+        check_columns(number, object.keys(), columns)?;
+        let mut cells = HashMap::new();
+        for (column, value) in object.iter() {
+            let cell = match value {
+                Value::String(s) => s.clone(),
+                Value::Number(n) => n.to_string(),
+                Value::Bool(b) => if *b { "1" } else { "0" }.to_string(),
+                Value::Null => String::new(),
+                _ => {
+                    return Err(MulReaderError::InvalidRow {
+                        row: number,
+                        message: format!("Invalid value for {}", column),
+                    });
+                }
+            };
+            cells.insert(column.clone(), cell);
+        }
+        rows.push(Row { number, cells });
+    }
+    Ok(rows)
+}
+
+/// Export map tiles as a JSON array, using their position in the slice as the id
+#[cfg(feature = "json")]
+pub fn map_tiles_to_json(tiles: &[MapTileData]) -> String {
+    write_json(
+        &MAP_COLUMNS,
+        tiles
+            .iter()
+            .enumerate()
+            .map(|(id, tile)| map_tile_row(id, tile))
+            .collect(),
+    )
+}
+
+/// Export static tiles as a JSON array, using their position in the slice as the id
+#[cfg(feature = "json")]
+pub fn static_tiles_to_json(tiles: &[StaticTileData]) -> String {
+    write_json(
+        &STATIC_COLUMNS,
+        tiles
+            .iter()
+            .enumerate()
+            .map(|(id, tile)| static_tile_row(id, tile))
+            .collect(),
+    )
+}
+
+/// Import map tiles from a JSON array, returning each with its id
+#[cfg(feature = "json")]
+pub fn map_tiles_from_json(data: &str) -> MulReaderResult<Vec<(u32, MapTileData)>> {
+    read_json(data, &MAP_COLUMNS)?
+        .iter()
+        .map(|row| row.to_map_tile())
+        .collect()
+}
+
+/// Import static tiles from a JSON array, returning each with its id
+#[cfg(feature = "json")]
+pub fn static_tiles_from_json(data: &str) -> MulReaderResult<Vec<(u32, StaticTileData)>> {
+    read_json(data, &STATIC_COLUMNS)?
+        .iter()
+        .map(|row| row.to_static_tile())
+        .collect()
+}
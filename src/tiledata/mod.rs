@@ -0,0 +1,920 @@
+//! Methods for reading and writing supplementary data about tiles and statics in tiledata.mul
+//!
+//! `tiledata` is split in two, much like `art`; first with those for map tiles, then statics.
+//!
+//! The file starts with 512 blocks of MapTileData, which are defined as
+//!
+//! `|unknown:u32|tiles:[MapTileData..32]|`
+//!
+//! MapTileData is defined as
+//!
+//! `|flags:u32|texture_id:u16|name:[u8..20(CString)]|`
+//!
+//! The rest of the file is Static tile data, also organised into blocks
+//!
+//! `|unknown:u32|tiles:[StaticTileData..32]|`
+//!
+//! StaticTileData is defined as
+//!
+//! `|flags:u32|weight:u8|quality:u8|unknown:u16|unknown:u8|quantity:u8|anim_id:u16|unknown:u8|hue:u8|unknown:u16|height:u8|name:[u8..20(CString)]|`
+//!
+//! From the High Seas client (7.0.9) onwards, `flags` is a u64 in both kinds of entry, and the
+//! number of static blocks grows. The layout in use is detected from the size of the file.
+//!
+//! Several fields are used to represent multiple attributes:
+//!
+//! * Quality also represents Layer for wearables, and Light ID for lights
+//! * Quantity represents Weapon Class for weapons, and Armor value for Armor
+//! * Height represents capacity for containers
+//!
+//! `StaticTileData` has accessors which pick the right interpretation based on its flags.
+//!
+//! For repeated lookups, `TileDataReader::read_all` loads the whole file into a searchable `TileData`.
+use bitflags::bitflags;
+use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
+use std::fs::{File, OpenOptions};
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::Path;
+
+use crate::error::{
+    MEMWRITER_ERROR, MulReaderError, MulReaderResult, MulWriterError, MulWriterResult,
+};
+use crate::mul::MulWriterMode;
+
+pub mod interchange;
+pub mod table;
+
+pub use crate::tiledata::table::TileData;
+
+bitflags! {
+    /// Bitflags associated with a tile
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+    pub struct Flags: u64 {
+        const BackgroundFlag = 0x00000001;
+        const WeaponFlag = 0x00000002;
+        const TransparentFlag = 0x00000004;
+        const TranslucentFlag = 0x00000008;
+        const WallFlag = 0x00000010;
+        const DamagingFlag = 0x00000020;
+        const ImpassableFlag = 0x00000040;
+        const WetFlag = 0x00000080;
+        const UnknownFlag = 0x00000100;
+        const SurfaceFlag = 0x00000200;
+        const BridgeFlag = 0x00000400;
+        const StackableFlag = 0x00000800;
+        const WindowFlag = 0x00001000;
+        const NoShootFlag = 0x00002000;
+        const PrefixAFlag = 0x00004000;
+        const PrefixAnFlag = 0x00008000;
+        const InternalFlag = 0x00010000;
+        const FoliageFlag = 0x00020000;
+        const PartialHueFlag = 0x00040000;
+        const Unknown1Flag = 0x00080000;
+        const MapFlag = 0x00100000;
+        const ContainerFlag = 0x00200000;
+        const WearableFlag = 0x00400000;
+        const LightSourceFlag = 0x00800000;
+        const AnimatedFlag = 0x01000000;
+        const NoDiagonalFlag = 0x02000000;
+        const Unknown2Flag = 0x04000000;
+        const ArmorFlag = 0x08000000;
+        const RoofFlag = 0x10000000;
+        const DoorFlag = 0x20000000;
+        const StairBackFlag = 0x40000000;
+        const StairRightFlag = 0x80000000;
+        // The following flags only exist in High Seas tiledata
+        const AlphaBlendFlag = 0x0000000100000000;
+        const UseNewArtFlag = 0x0000000200000000;
+        const ArtUsedFlag = 0x0000000400000000;
+        const Unknown3Flag = 0x0000000800000000;
+        const NoShadowFlag = 0x0000001000000000;
+        const PixelBleedFlag = 0x0000002000000000;
+        const PlayAnimOnceFlag = 0x0000004000000000;
+        const Unknown4Flag = 0x0000008000000000;
+        const MultiMovableFlag = 0x0000010000000000;
+    }
+}
+
+// Tile data is odd, as we have [(unknown, (LAND_TILE_DATA) * 32) * 512]
+// All values are in bytes
+const GROUP_HEADER_SIZE: u32 = 4;
+const GROUP_LENGTH: u32 = 32;
+const MAP_TILE_GROUPS: u32 = 512;
+// Item ids are u16s, so no client has more statics than this
+const MAX_STATIC_TILES: u32 = 0x10000;
+const NAME_SIZE: usize = 20;
+// The smallest High Seas tiledata shipped, used when the size alone is ambiguous
+const HIGH_SEAS_MIN_SIZE: u64 = 3188736;
+
+/// The two known tiledata layouts
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TileDataLayout {
+    /// Clients before 7.0.9, with 32-bit flags
+    Classic,
+    /// High Seas clients from 7.0.9, with 64-bit flags
+    HighSeas,
+}
+
+impl TileDataLayout {
+    /// Guess the layout from the total length of tiledata.mul
+    ///
+    /// A file that divides cleanly into whole blocks of only one layout is that layout.
+    /// Otherwise, very large files are assumed to be High Seas.
+    pub fn detect(length: u64) -> TileDataLayout {
+        let fits = |layout: TileDataLayout| {
+            let map_size = layout.map_section_size();
+            length >= map_size
+                && (length - map_size).is_multiple_of(layout.static_group_size() as u64)
+        };
+        match (
+            fits(TileDataLayout::Classic),
+            fits(TileDataLayout::HighSeas),
+        ) {
+            (true, false) => TileDataLayout::Classic,
+            (false, true) => TileDataLayout::HighSeas,
+            _ if length >= HIGH_SEAS_MIN_SIZE => TileDataLayout::HighSeas,
+            _ => TileDataLayout::Classic,
+        }
+    }
+
+    /// The size of the flags field, in bytes
+    pub fn flags_size(&self) -> u32 {
+        match self {
+            TileDataLayout::Classic => 4,
+            TileDataLayout::HighSeas => 8,
+        }
+    }
+
+    /// The size of a single MapTileData entry, in bytes
+    pub fn map_tile_size(&self) -> u32 {
+        self.flags_size() + 2 + NAME_SIZE as u32
+    }
+
+    /// The size of a single StaticTileData entry, in bytes
+    pub fn static_tile_size(&self) -> u32 {
+        self.flags_size() + 13 + NAME_SIZE as u32
+    }
+
+    fn map_section_size(&self) -> u64 {
+        (MAP_TILE_GROUPS * self.map_group_size()) as u64
+    }
+
+    fn map_group_size(&self) -> u32 {
+        GROUP_HEADER_SIZE + GROUP_LENGTH * self.map_tile_size()
+    }
+
+    fn static_group_size(&self) -> u32 {
+        GROUP_HEADER_SIZE + GROUP_LENGTH * self.static_tile_size()
+    }
+
+    fn map_group_offset(&self, group: u32) -> u64 {
+        (group * self.map_group_size()) as u64
+    }
+
+    fn map_tile_offset(&self, idx: u32) -> u64 {
+        //For every 32, we have to add an unknown header
+        self.map_group_offset(idx / GROUP_LENGTH)
+            + (GROUP_HEADER_SIZE + (idx % GROUP_LENGTH) * self.map_tile_size()) as u64
+    }
+
+    fn static_group_offset(&self, group: u32) -> u64 {
+        self.map_section_size() + group as u64 * self.static_group_size() as u64
+    }
+
+    fn static_tile_offset(&self, idx: u32) -> u64 {
+        self.static_group_offset(idx / GROUP_LENGTH)
+            + (GROUP_HEADER_SIZE + (idx % GROUP_LENGTH) * self.static_tile_size()) as u64
+    }
+}
+
+fn write_flags(writer: &mut Vec<u8>, flags: Flags, layout: TileDataLayout) {
+    match layout {
+        TileDataLayout::Classic => writer
+            .write_u32::<LittleEndian>(flags.bits() as u32)
+            .expect(MEMWRITER_ERROR),
+        TileDataLayout::HighSeas => writer
+            .write_u64::<LittleEndian>(flags.bits())
+            .expect(MEMWRITER_ERROR),
+    }
+}
+
+/// Get the text of a stored name, up to its null terminator
+fn decode_name(raw_name: &[u8; NAME_SIZE]) -> String {
+    let length = raw_name.iter().position(|&c| c == 0).unwrap_or(NAME_SIZE);
+    String::from_utf8_lossy(&raw_name[..length]).into_owned()
+}
+
+/// Write the stored name if the name hasn't been changed, otherwise the new name padded with nulls
+fn write_name(writer: &mut Vec<u8>, name: &str, raw_name: &[u8; NAME_SIZE]) {
+    if decode_name(raw_name) == name {
+        writer.write_all(raw_name).expect(MEMWRITER_ERROR);
+        return;
+    }
+    writer.write_all(name.as_bytes()).expect(MEMWRITER_ERROR);
+    writer
+        .write_all(vec![0; NAME_SIZE - name.len()].as_slice())
+        .expect(MEMWRITER_ERROR);
+}
+
+fn validate_name(name: &str, raw_name: &[u8; NAME_SIZE]) -> MulWriterResult<()> {
+    //An unchanged name is written back exactly as it was read
+    if decode_name(raw_name) == name {
+        return Ok(());
+    }
+    if !name.is_ascii() {
+        return Err(MulWriterError::NonAsciiName(name.to_string()));
+    }
+    if name.len() > NAME_SIZE {
+        return Err(MulWriterError::NameTooLong {
+            name: name.to_string(),
+            max: NAME_SIZE,
+        });
+    }
+    Ok(())
+}
+
+fn validate_group_length(length: usize) -> MulWriterResult<()> {
+    if length != GROUP_LENGTH as usize {
+        return Err(MulWriterError::UnexpectedSize {
+            found: length as u32,
+            expected: GROUP_LENGTH,
+        });
+    }
+    Ok(())
+}
+
+/// The equipment slot a wearable occupies
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Layer {
+    OneHanded = 1,
+    TwoHanded = 2,
+    Shoes = 3,
+    Pants = 4,
+    Shirt = 5,
+    Helm = 6,
+    Gloves = 7,
+    Ring = 8,
+    Talisman = 9,
+    Neck = 10,
+    Hair = 11,
+    Waist = 12,
+    InnerTorso = 13,
+    Bracelet = 14,
+    Face = 15,
+    FacialHair = 16,
+    MiddleTorso = 17,
+    Earrings = 18,
+    Arms = 19,
+    Cloak = 20,
+    Backpack = 21,
+    OuterTorso = 22,
+    OuterLegs = 23,
+    InnerLegs = 24,
+    Mount = 25,
+    ShopBuy = 26,
+    ShopResale = 27,
+    ShopSell = 28,
+    Bank = 29,
+}
+
+impl Layer {
+    /// Look up a layer from its raw id
+    pub fn from_id(id: u8) -> Option<Layer> {
+        match id {
+            1 => Some(Layer::OneHanded),
+            2 => Some(Layer::TwoHanded),
+            3 => Some(Layer::Shoes),
+            4 => Some(Layer::Pants),
+            5 => Some(Layer::Shirt),
+            6 => Some(Layer::Helm),
+            7 => Some(Layer::Gloves),
+            8 => Some(Layer::Ring),
+            9 => Some(Layer::Talisman),
+            10 => Some(Layer::Neck),
+            11 => Some(Layer::Hair),
+            12 => Some(Layer::Waist),
+            13 => Some(Layer::InnerTorso),
+            14 => Some(Layer::Bracelet),
+            15 => Some(Layer::Face),
+            16 => Some(Layer::FacialHair),
+            17 => Some(Layer::MiddleTorso),
+            18 => Some(Layer::Earrings),
+            19 => Some(Layer::Arms),
+            20 => Some(Layer::Cloak),
+            21 => Some(Layer::Backpack),
+            22 => Some(Layer::OuterTorso),
+            23 => Some(Layer::OuterLegs),
+            24 => Some(Layer::InnerLegs),
+            25 => Some(Layer::Mount),
+            26 => Some(Layer::ShopBuy),
+            27 => Some(Layer::ShopResale),
+            28 => Some(Layer::ShopSell),
+            29 => Some(Layer::Bank),
+            _ => None,
+        }
+    }
+
+    /// The raw id of this layer
+    pub fn id(&self) -> u8 {
+        *self as u8
+    }
+}
+
+/// How the quantity field should be read for weapons and armor
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CombatClass {
+    /// The raw weapon class of an item flagged as a weapon
+    Weapon(u8),
+    /// The raw armor class of an item flagged as armor
+    Armor(u8),
+}
+
+/// The indefinite article used when naming an item
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Article {
+    A,
+    An,
+}
+
+impl Article {
+    /// The article as it should be written
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Article::A => "a",
+            Article::An => "an",
+        }
+    }
+}
+
+/// Information about a given Map tile
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct MapTileData {
+    pub flags: Flags,
+    /// Which TexMap to use instead if this tile is non-flat
+    pub texture_id: u16,
+    pub name: String,
+    /// The name as stored, including anything after its null terminator, or zeroed for new tiles.
+    /// This is written back in place of `name` for as long as `name` is left unchanged
+    pub raw_name: [u8; NAME_SIZE],
+}
+
+impl MapTileData {
+    /// Check that the name is ASCII, and fits in the 20 bytes available
+    pub fn validate_name(&self) -> MulWriterResult<()> {
+        validate_name(&self.name, &self.raw_name)
+    }
+
+    /// Convert map tile data back into its canonical mul representation.
+    ///
+    /// High Seas flags are dropped when writing the classic layout.
+    /// This will panic if the name is too long to fit - use `validate_name` to check beforehand
+    pub fn serialize(&self, layout: TileDataLayout) -> Vec<u8> {
+        let mut writer = vec![];
+        write_flags(&mut writer, self.flags, layout);
+        writer
+            .write_u16::<LittleEndian>(self.texture_id)
+            .expect(MEMWRITER_ERROR);
+        write_name(&mut writer, &self.name, &self.raw_name);
+        writer
+    }
+}
+
+/// Information about a given Static tile
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct StaticTileData {
+    pub flags: Flags,
+    pub weight: u8,
+    /// This field becomes Layer for wearables, and the Light ID for lights, otherwise quality.
+    pub quality_layer_light_id: u8,
+    /// Unknown usage
+    pub unknown: u16,
+    /// Unknown usage
+    pub unknown1: u8,
+    /// This field becomes weapon class for weapons, armor class for armor, or defaults to quantity.
+    pub quantity_weapon_class_armor_class: u8,
+    pub anim_id: u16,
+    /// Unknown usage
+    pub unknown2: u8,
+    pub hue: u8,
+    /// Unknown usage
+    pub unknown3: u16,
+    /// This field becomes capacity for containers, otherwise height
+    pub height_capacity: u8,
+    pub name: String,
+    /// The name as stored, including anything after its null terminator, or zeroed for new tiles.
+    /// This is written back in place of `name` for as long as `name` is left unchanged
+    pub raw_name: [u8; NAME_SIZE],
+}
+
+impl StaticTileData {
+    /// The layer this item is worn on, if it is a wearable with a known layer
+    pub fn layer(&self) -> Option<Layer> {
+        if self.flags.contains(Flags::WearableFlag) {
+            Layer::from_id(self.quality_layer_light_id)
+        } else {
+            None
+        }
+    }
+
+    /// The light shape this item emits, if it is a light source
+    pub fn light_id(&self) -> Option<u8> {
+        if self.flags.contains(Flags::LightSourceFlag) {
+            Some(self.quality_layer_light_id)
+        } else {
+            None
+        }
+    }
+
+    /// The item's quality, if the field isn't being used as a layer or light id
+    pub fn quality(&self) -> Option<u8> {
+        if self
+            .flags
+            .intersects(Flags::WearableFlag | Flags::LightSourceFlag)
+        {
+            None
+        } else {
+            Some(self.quality_layer_light_id)
+        }
+    }
+
+    /// The weapon or armor class, if this item is a weapon or armor.
+    ///
+    /// Items flagged as both are treated as weapons
+    pub fn combat_class(&self) -> Option<CombatClass> {
+        if self.flags.contains(Flags::WeaponFlag) {
+            Some(CombatClass::Weapon(self.quantity_weapon_class_armor_class))
+        } else if self.flags.contains(Flags::ArmorFlag) {
+            Some(CombatClass::Armor(self.quantity_weapon_class_armor_class))
+        } else {
+            None
+        }
+    }
+
+    /// The item's quantity, if the field isn't being used as a weapon or armor class
+    pub fn quantity(&self) -> Option<u8> {
+        if self.flags.intersects(Flags::WeaponFlag | Flags::ArmorFlag) {
+            None
+        } else {
+            Some(self.quantity_weapon_class_armor_class)
+        }
+    }
+
+    /// How much a container can hold, if this item is a container
+    pub fn capacity(&self) -> Option<u8> {
+        if self.flags.contains(Flags::ContainerFlag) {
+            Some(self.height_capacity)
+        } else {
+            None
+        }
+    }
+
+    /// The physical height of the item, unless it is a container
+    pub fn height(&self) -> Option<u8> {
+        if self.flags.contains(Flags::ContainerFlag) {
+            None
+        } else {
+            Some(self.height_capacity)
+        }
+    }
+
+    /// The indefinite article for this item's name, if it takes one
+    pub fn article(&self) -> Option<Article> {
+        if self.flags.contains(Flags::PrefixAnFlag) {
+            Some(Article::An)
+        } else if self.flags.contains(Flags::PrefixAFlag) {
+            Some(Article::A)
+        } else {
+            None
+        }
+    }
+
+    /// The item's name, prefixed with its article if it has one
+    pub fn name_with_article(&self) -> String {
+        match self.article() {
+            Some(article) => format!("{} {}", article.as_str(), self.name),
+            None => self.name.clone(),
+        }
+    }
+
+    /// Check that the name is ASCII, and fits in the 20 bytes available
+    pub fn validate_name(&self) -> MulWriterResult<()> {
+        validate_name(&self.name, &self.raw_name)
+    }
+
+    /// Convert static tile data back into its canonical mul representation.
+    ///
+    /// High Seas flags are dropped when writing the classic layout.
+    /// This will panic if the name is too long to fit - use `validate_name` to check beforehand
+    pub fn serialize(&self, layout: TileDataLayout) -> Vec<u8> {
+        let mut writer = vec![];
+        write_flags(&mut writer, self.flags, layout);
+        writer.write_u8(self.weight).expect(MEMWRITER_ERROR);
+        writer
+            .write_u8(self.quality_layer_light_id)
+            .expect(MEMWRITER_ERROR);
+        writer
+            .write_u16::<LittleEndian>(self.unknown)
+            .expect(MEMWRITER_ERROR);
+        writer.write_u8(self.unknown1).expect(MEMWRITER_ERROR);
+        writer
+            .write_u8(self.quantity_weapon_class_armor_class)
+            .expect(MEMWRITER_ERROR);
+        writer
+            .write_u16::<LittleEndian>(self.anim_id)
+            .expect(MEMWRITER_ERROR);
+        writer.write_u8(self.unknown2).expect(MEMWRITER_ERROR);
+        writer.write_u8(self.hue).expect(MEMWRITER_ERROR);
+        writer
+            .write_u16::<LittleEndian>(self.unknown3)
+            .expect(MEMWRITER_ERROR);
+        writer
+            .write_u8(self.height_capacity)
+            .expect(MEMWRITER_ERROR);
+        write_name(&mut writer, &self.name, &self.raw_name);
+        writer
+    }
+}
+
+/// A block of 32 MapTileData entries
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct MapTileGroup {
+    ///Unknown usage
+    pub header: u32,
+    pub entries: Vec<MapTileData>,
+}
+
+impl MapTileGroup {
+    /// Convert a group back into its canonical mul representation
+    pub fn serialize(&self, layout: TileDataLayout) -> Vec<u8> {
+        let mut writer = vec![];
+        writer
+            .write_u32::<LittleEndian>(self.header)
+            .expect(MEMWRITER_ERROR);
+        for entry in self.entries.iter() {
+            writer
+                .write_all(&entry.serialize(layout))
+                .expect(MEMWRITER_ERROR);
+        }
+        writer
+    }
+
+    fn validate(&self) -> MulWriterResult<()> {
+        validate_group_length(self.entries.len())?;
+        for entry in self.entries.iter() {
+            entry.validate_name()?;
+        }
+        Ok(())
+    }
+}
+
+/// A block of 32 StaticTileData entries
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct StaticTileGroup {
+    ///Unknown usage
+    pub header: u32,
+    pub entries: Vec<StaticTileData>,
+}
+
+impl StaticTileGroup {
+    /// Convert a group back into its canonical mul representation
+    pub fn serialize(&self, layout: TileDataLayout) -> Vec<u8> {
+        let mut writer = vec![];
+        writer
+            .write_u32::<LittleEndian>(self.header)
+            .expect(MEMWRITER_ERROR);
+        for entry in self.entries.iter() {
+            writer
+                .write_all(&entry.serialize(layout))
+                .expect(MEMWRITER_ERROR);
+        }
+        writer
+    }
+
+    fn validate(&self) -> MulWriterResult<()> {
+        validate_group_length(self.entries.len())?;
+        for entry in self.entries.iter() {
+            entry.validate_name()?;
+        }
+        Ok(())
+    }
+}
+
+/// A struct to help read out MapTileData and StaticTileData data
+#[derive(Debug)]
+pub struct TileDataReader<T: Read + Seek> {
+    data_reader: T,
+    layout: TileDataLayout,
+    length: u64,
+}
+
+impl TileDataReader<File> {
+    /// Create a new TileDataReader from a mul path
+    pub fn new(mul_path: &Path) -> MulReaderResult<TileDataReader<File>> {
+        let data_reader = File::open(mul_path)?;
+
+        TileDataReader::from_readable(data_reader)
+    }
+}
+
+impl<T: Read + Seek> TileDataReader<T> {
+    /// Create a TileDataReader from an existing file reader, detecting the layout from its length
+    pub fn from_readable(mut reader: T) -> MulReaderResult<TileDataReader<T>> {
+        let length = reader.seek(SeekFrom::End(0))?;
+        Ok(TileDataReader {
+            data_reader: reader,
+            layout: TileDataLayout::detect(length),
+            length,
+        })
+    }
+
+    /// Create a TileDataReader from an existing file reader, with a known layout
+    pub fn from_readable_with_layout(
+        mut reader: T,
+        layout: TileDataLayout,
+    ) -> MulReaderResult<TileDataReader<T>> {
+        let length = reader.seek(SeekFrom::End(0))?;
+        Ok(TileDataReader {
+            data_reader: reader,
+            layout,
+            length,
+        })
+    }
+
+    /// The layout this reader is using
+    pub fn layout(&self) -> TileDataLayout {
+        self.layout
+    }
+
+    /// The number of map tiles in the file
+    pub fn map_tile_count(&self) -> u32 {
+        MAP_TILE_GROUPS * GROUP_LENGTH
+    }
+
+    /// The number of static tiles in the file. Incomplete trailing blocks are ignored
+    pub fn static_tile_count(&self) -> u32 {
+        let static_section = self.length.saturating_sub(self.layout.map_section_size());
+        (static_section / self.layout.static_group_size() as u64) as u32 * GROUP_LENGTH
+    }
+
+    fn read_flags(&mut self) -> MulReaderResult<Flags> {
+        let bits = match self.layout {
+            TileDataLayout::Classic => self.data_reader.read_u32::<LittleEndian>()? as u64,
+            TileDataLayout::HighSeas => self.data_reader.read_u64::<LittleEndian>()?,
+        };
+        Ok(Flags::from_bits_retain(bits))
+    }
+
+    fn read_name(&mut self) -> MulReaderResult<(String, [u8; NAME_SIZE])> {
+        let mut raw_name = [0; NAME_SIZE];
+        self.data_reader.read_exact(&mut raw_name)?;
+        Ok((decode_name(&raw_name), raw_name))
+    }
+
+    /// Read a map tile's associated data.
+    ///
+    /// The ID matches the data in ArtReader's `read_tile`
+    pub fn read_map_tile_data(&mut self, idx: u32) -> MulReaderResult<MapTileData> {
+        if idx >= self.map_tile_count() {
+            return Err(MulReaderError::IndexOutOfBounds(idx));
+        }
+        let offset = self.layout.map_tile_offset(idx);
+        self.data_reader.seek(SeekFrom::Start(offset))?;
+        self.read_map_tile_entry()
+    }
+
+    fn read_map_tile_entry(&mut self) -> MulReaderResult<MapTileData> {
+        let flags = self.read_flags()?;
+        let texture_id = self.data_reader.read_u16::<LittleEndian>()?;
+        let (name, raw_name) = self.read_name()?;
+
+        Ok(MapTileData {
+            flags,
+            texture_id,
+            name,
+            raw_name,
+        })
+    }
+
+    /// Read a block of 32 map tiles, along with its header
+    pub fn read_map_tile_group(&mut self, group: u32) -> MulReaderResult<MapTileGroup> {
+        if group >= MAP_TILE_GROUPS {
+            return Err(MulReaderError::IndexOutOfBounds(group));
+        }
+        let offset = self.layout.map_group_offset(group);
+        self.data_reader.seek(SeekFrom::Start(offset))?;
+        let header = self.data_reader.read_u32::<LittleEndian>()?;
+        let mut entries = vec![];
+        for _ in 0..GROUP_LENGTH {
+            entries.push(self.read_map_tile_entry()?);
+        }
+        Ok(MapTileGroup { header, entries })
+    }
+
+    /// Read a static tile's associated data.
+    ///
+    /// The ID is read from the static offset, and matches the data in ArtReader's `read_static`
+    pub fn read_static_tile_data(&mut self, idx: u32) -> MulReaderResult<StaticTileData> {
+        if idx >= self.static_tile_count() {
+            return Err(MulReaderError::IndexOutOfBounds(idx));
+        }
+        let offset = self.layout.static_tile_offset(idx);
+        self.data_reader.seek(SeekFrom::Start(offset))?;
+        self.read_static_tile_entry()
+    }
+
+    fn read_static_tile_entry(&mut self) -> MulReaderResult<StaticTileData> {
+        let flags = self.read_flags()?;
+        let weight = self.data_reader.read_u8()?;
+        let quality = self.data_reader.read_u8()?;
+        let unknown = self.data_reader.read_u16::<LittleEndian>()?;
+        let unknown1 = self.data_reader.read_u8()?;
+        let quantity = self.data_reader.read_u8()?;
+        let anim_id = self.data_reader.read_u16::<LittleEndian>()?;
+        let unknown2 = self.data_reader.read_u8()?;
+        let hue = self.data_reader.read_u8()?;
+        let unknown3 = self.data_reader.read_u16::<LittleEndian>()?;
+        let height = self.data_reader.read_u8()?;
+        let (name, raw_name) = self.read_name()?;
+
+        Ok(StaticTileData {
+            flags,
+            weight,
+            quality_layer_light_id: quality,
+            unknown,
+            unknown1,
+            quantity_weapon_class_armor_class: quantity,
+            anim_id,
+            unknown2,
+            hue,
+            unknown3,
+            height_capacity: height,
+            name,
+            raw_name,
+        })
+    }
+
+    /// Read a block of 32 static tiles, along with its header
+    pub fn read_static_tile_group(&mut self, group: u32) -> MulReaderResult<StaticTileGroup> {
+        if group >= self.static_tile_count() / GROUP_LENGTH {
+            return Err(MulReaderError::IndexOutOfBounds(group));
+        }
+        let offset = self.layout.static_group_offset(group);
+        self.data_reader.seek(SeekFrom::Start(offset))?;
+        let header = self.data_reader.read_u32::<LittleEndian>()?;
+        let mut entries = vec![];
+        for _ in 0..GROUP_LENGTH {
+            entries.push(self.read_static_tile_entry()?);
+        }
+        Ok(StaticTileGroup { header, entries })
+    }
+
+    /// Read every map and static tile into memory
+    pub fn read_all(&mut self) -> MulReaderResult<TileData> {
+        let mut map_tiles = vec![];
+        for group in 0..MAP_TILE_GROUPS {
+            map_tiles.extend(self.read_map_tile_group(group)?.entries);
+        }
+        let mut static_tiles = vec![];
+        for group in 0..(self.static_tile_count() / GROUP_LENGTH) {
+            static_tiles.extend(self.read_static_tile_group(group)?.entries);
+        }
+        Ok(TileData::new(map_tiles, static_tiles))
+    }
+}
+
+/// A struct to help write MapTileData and StaticTileData, either as a whole file or by
+/// editing individual entries in place
+#[derive(Debug)]
+pub struct TileDataWriter<T: Write + Seek> {
+    data_writer: T,
+    layout: TileDataLayout,
+}
+
+impl TileDataWriter<File> {
+    /// Create a new TileDataWriter from a mul path.
+    ///
+    /// `MulWriterMode::Append` keeps the existing contents, so that entries can be edited in place
+    pub fn new(
+        mul_path: &Path,
+        layout: TileDataLayout,
+        mode: MulWriterMode,
+    ) -> MulWriterResult<TileDataWriter<File>> {
+        let data_writer = OpenOptions::new()
+            .write(true)
+            .create(true)
+            .truncate(match mode {
+                MulWriterMode::Append => false,
+                MulWriterMode::Truncate => true,
+            })
+            .open(mul_path)?;
+
+        Ok(TileDataWriter {
+            data_writer,
+            layout,
+        })
+    }
+}
+
+impl<T: Write + Seek> TileDataWriter<T> {
+    /// Create a TileDataWriter from an existing file writer
+    pub fn from_writable(data_writer: T, layout: TileDataLayout) -> TileDataWriter<T> {
+        TileDataWriter {
+            data_writer,
+            layout,
+        }
+    }
+
+    /// The layout this writer is using
+    pub fn layout(&self) -> TileDataLayout {
+        self.layout
+    }
+
+    /// Write a complete tiledata file.
+    ///
+    /// There must be exactly 512 map groups, and every group must contain 32 entries
+    pub fn write_all(
+        &mut self,
+        map_groups: &[MapTileGroup],
+        static_groups: &[StaticTileGroup],
+    ) -> MulWriterResult<()> {
+        if map_groups.len() != MAP_TILE_GROUPS as usize {
+            return Err(MulWriterError::UnexpectedSize {
+                found: map_groups.len() as u32,
+                expected: MAP_TILE_GROUPS,
+            });
+        }
+        for group in map_groups.iter() {
+            group.validate()?;
+        }
+        for group in static_groups.iter() {
+            group.validate()?;
+        }
+
+        self.data_writer.seek(SeekFrom::Start(0))?;
+        for group in map_groups.iter() {
+            self.data_writer.write_all(&group.serialize(self.layout))?;
+        }
+        for group in static_groups.iter() {
+            self.data_writer.write_all(&group.serialize(self.layout))?;
+        }
+        Ok(())
+    }
+
+    /// Replace a block of 32 map tiles, including its header
+    pub fn write_map_tile_group(&mut self, group: u32, data: &MapTileGroup) -> MulWriterResult<()> {
+        if group >= MAP_TILE_GROUPS {
+            return Err(MulWriterError::IndexOutOfBounds(group));
+        }
+        data.validate()?;
+        self.data_writer
+            .seek(SeekFrom::Start(self.layout.map_group_offset(group)))?;
+        self.data_writer.write_all(&data.serialize(self.layout))?;
+        Ok(())
+    }
+
+    /// Replace a block of 32 static tiles, including its header
+    pub fn write_static_tile_group(
+        &mut self,
+        group: u32,
+        data: &StaticTileGroup,
+    ) -> MulWriterResult<()> {
+        if group >= MAX_STATIC_TILES / GROUP_LENGTH {
+            return Err(MulWriterError::IndexOutOfBounds(group));
+        }
+        data.validate()?;
+        self.data_writer
+            .seek(SeekFrom::Start(self.layout.static_group_offset(group)))?;
+        self.data_writer.write_all(&data.serialize(self.layout))?;
+        Ok(())
+    }
+
+    /// Replace a single map tile's data, leaving the rest of the file untouched
+    pub fn write_map_tile_data(&mut self, idx: u32, data: &MapTileData) -> MulWriterResult<()> {
+        if idx >= MAP_TILE_GROUPS * GROUP_LENGTH {
+            return Err(MulWriterError::IndexOutOfBounds(idx));
+        }
+        data.validate_name()?;
+        self.data_writer
+            .seek(SeekFrom::Start(self.layout.map_tile_offset(idx)))?;
+        self.data_writer.write_all(&data.serialize(self.layout))?;
+        Ok(())
+    }
+
+    /// Replace a single static tile's data, leaving the rest of the file untouched
+    ///
+    /// The ID is read from the static offset, as with `TileDataReader::read_static_tile_data`
+    pub fn write_static_tile_data(
+        &mut self,
+        idx: u32,
+        data: &StaticTileData,
+    ) -> MulWriterResult<()> {
+        if idx >= MAX_STATIC_TILES {
+            return Err(MulWriterError::IndexOutOfBounds(idx));
+        }
+        data.validate_name()?;
+        self.data_writer
+            .seek(SeekFrom::Start(self.layout.static_tile_offset(idx)))?;
+        self.data_writer.write_all(&data.serialize(self.layout))?;
+        Ok(())
+    }
+}
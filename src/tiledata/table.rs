@@ -0,0 +1,124 @@
+//! An in-memory table of tiledata, for fast lookups and searches
+//!
+//! All searches return ids, which match those used by `ArtReader` and `TileDataReader`.
+//! Static ids are counted from the static offset, so 0 is the first static.
+#[cfg(feature = "regex")]
+use regex::Regex;
+use std::collections::HashMap;
+
+use super::{Flags, Layer, MapTileData, StaticTileData};
+
+/// Every map and static tile's data, loaded at once.
+///
+/// The tiles can't be changed in place, as lookups are built from them up front
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct TileData {
+    map_tiles: Vec<MapTileData>,
+    static_tiles: Vec<StaticTileData>,
+    anim_lookup: HashMap<u16, Vec<u32>>,
+}
+
+impl TileData {
+    /// Create a table from lists of map and static tile data, indexed by their position
+    pub fn new(map_tiles: Vec<MapTileData>, static_tiles: Vec<StaticTileData>) -> TileData {
+        let mut anim_lookup: HashMap<u16, Vec<u32>> = HashMap::new();
+        for (id, tile) in static_tiles.iter().enumerate() {
+            //An anim_id of 0 means the item has no animation
+            if tile.anim_id != 0 {
+                anim_lookup.entry(tile.anim_id).or_default().push(id as u32);
+            }
+        }
+        TileData {
+            map_tiles,
+            static_tiles,
+            anim_lookup,
+        }
+    }
+
+    /// Every map tile's data, indexed by id
+    pub fn map_tiles(&self) -> &[MapTileData] {
+        &self.map_tiles
+    }
+
+    /// Every static tile's data, indexed by id
+    pub fn static_tiles(&self) -> &[StaticTileData] {
+        &self.static_tiles
+    }
+
+    /// Get a map tile's data by id
+    pub fn map_tile(&self, id: u32) -> Option<&MapTileData> {
+        self.map_tiles.get(id as usize)
+    }
+
+    /// Get a static tile's data by id
+    pub fn static_tile(&self, id: u32) -> Option<&StaticTileData> {
+        self.static_tiles.get(id as usize)
+    }
+
+    /// Find all map tiles matching a predicate
+    pub fn filter_map_tiles<F: Fn(&MapTileData) -> bool>(&self, predicate: F) -> Vec<u32> {
+        self.map_tiles
+            .iter()
+            .enumerate()
+            .filter(|(_, tile)| predicate(tile))
+            .map(|(id, _)| id as u32)
+            .collect()
+    }
+
+    /// Find all static tiles matching a predicate
+    pub fn filter_static_tiles<F: Fn(&StaticTileData) -> bool>(&self, predicate: F) -> Vec<u32> {
+        self.static_tiles
+            .iter()
+            .enumerate()
+            .filter(|(_, tile)| predicate(tile))
+            .map(|(id, _)| id as u32)
+            .collect()
+    }
+
+    /// Find all map tiles whose name contains the given text, ignoring case
+    pub fn find_map_tiles_by_name(&self, needle: &str) -> Vec<u32> {
+        let needle = needle.to_lowercase();
+        self.filter_map_tiles(|tile| tile.name.to_lowercase().contains(&needle))
+    }
+
+    /// Find all static tiles whose name contains the given text, ignoring case
+    pub fn find_static_tiles_by_name(&self, needle: &str) -> Vec<u32> {
+        let needle = needle.to_lowercase();
+        self.filter_static_tiles(|tile| tile.name.to_lowercase().contains(&needle))
+    }
+
+    /// Find all map tiles whose name matches a regular expression
+    #[cfg(feature = "regex")]
+    pub fn find_map_tiles_by_regex(&self, pattern: &Regex) -> Vec<u32> {
+        self.filter_map_tiles(|tile| pattern.is_match(&tile.name))
+    }
+
+    /// Find all static tiles whose name matches a regular expression
+    #[cfg(feature = "regex")]
+    pub fn find_static_tiles_by_regex(&self, pattern: &Regex) -> Vec<u32> {
+        self.filter_static_tiles(|tile| pattern.is_match(&tile.name))
+    }
+
+    /// Find all map tiles which have every one of the given flags set
+    pub fn map_tiles_with_flags(&self, flags: Flags) -> Vec<u32> {
+        self.filter_map_tiles(|tile| tile.flags.contains(flags))
+    }
+
+    /// Find all static tiles which have every one of the given flags set
+    pub fn static_tiles_with_flags(&self, flags: Flags) -> Vec<u32> {
+        self.filter_static_tiles(|tile| tile.flags.contains(flags))
+    }
+
+    /// Find all wearables that are worn on a given layer
+    pub fn wearables_on_layer(&self, layer: Layer) -> Vec<u32> {
+        self.filter_static_tiles(|tile| tile.layer() == Some(layer))
+    }
+
+    /// Find all static tiles that use a given animation
+    pub fn statics_for_anim(&self, anim_id: u16) -> &[u32] {
+        self.anim_lookup
+            .get(&anim_id)
+            .map(|ids| ids.as_slice())
+            .unwrap_or(&[])
+    }
+}